@@ -0,0 +1,71 @@
+use std::fmt::Write;
+
+/// Judges only deal in strings, so binary solutions travel between the main server, the
+/// lang runner and the judge as a string where every byte is mapped to the char with the
+/// same code point (U+0000 to U+00FF)
+pub fn to_binary_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|&byte| char::from(byte)).collect()
+}
+
+/// Reverses [`to_binary_string`]. Returns `None` if the string contains a char above
+/// U+00FF, which can happen if a judge modifies the code of a binary solution.
+pub fn from_binary_string(s: &str) -> Option<Vec<u8>> {
+    s.chars().map(|c| u8::try_from(c).ok()).collect()
+}
+
+const HEXDUMP_WIDTH: usize = 16;
+
+/// Formats bytes the same way `xxd` does, so the output can be pasted back in as a hex
+/// submission
+pub fn hexdump(bytes: &[u8]) -> String {
+    let mut output = String::new();
+    for (line_number, line) in bytes.chunks(HEXDUMP_WIDTH).enumerate() {
+        write!(output, "{:08x}:", line_number * HEXDUMP_WIDTH).unwrap();
+        for (index, byte) in line.iter().enumerate() {
+            if index % 2 == 0 {
+                output.push(' ');
+            }
+            write!(output, "{byte:02x}").unwrap();
+        }
+        let missing = HEXDUMP_WIDTH - line.len();
+        output.extend(std::iter::repeat_n(' ', missing * 2 + missing / 2 + 2));
+        output.extend(line.iter().map(|&byte| {
+            if byte.is_ascii_graphic() || byte == b' ' {
+                char::from(byte)
+            } else {
+                '.'
+            }
+        }));
+        output.push('\n');
+    }
+    output
+}
+
+/// Parses plain hex (whitespace is ignored) or the output of `xxd`/[`hexdump`]. For the
+/// latter the offsets and the text column are stripped.
+pub fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    let mut digits = String::new();
+    for line in hex.lines() {
+        let line = match line.split_once(':') {
+            Some((offset, rest))
+                if !offset.trim().is_empty()
+                    && offset.trim().chars().all(|c| c.is_ascii_hexdigit()) =>
+            {
+                // The text column is separated from the bytes by two spaces
+                rest.trim_start()
+                    .split_once("  ")
+                    .map_or(rest, |(bytes, _text)| bytes)
+            }
+            _ => line,
+        };
+        digits.extend(line.chars().filter(|c| !c.is_whitespace()));
+    }
+
+    if !digits.len().is_multiple_of(2) || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&digits[index..index + 2], 16).ok())
+        .collect()
+}
@@ -0,0 +1,91 @@
+use serde::Serialize;
+
+/// A single byte character set, the byte for each character is its index in `characters`
+#[derive(Serialize)]
+pub struct CodePage {
+    pub name: &'static str,
+    pub characters: &'static str,
+}
+
+/// The length of the UTF-8 sequence that starts with `byte`
+const fn utf8_len(byte: u8) -> usize {
+    match byte {
+        0x00..0x80 => 1,
+        0x80..0xe0 => 2,
+        0xe0..0xf0 => 3,
+        _ => 4,
+    }
+}
+
+const fn same_char(bytes: &[u8], a: usize, b: usize) -> bool {
+    let len = utf8_len(bytes[a]);
+    if len != utf8_len(bytes[b]) {
+        return false;
+    }
+    let mut index = 0;
+    while index < len {
+        if bytes[a + index] != bytes[b + index] {
+            return false;
+        }
+        index += 1;
+    }
+    true
+}
+
+impl CodePage {
+    /// Fails to compile unless `characters` has 256 distinct characters, one for each byte
+    pub const fn new(name: &'static str, characters: &'static str) -> CodePage {
+        let bytes = characters.as_bytes();
+        let mut count = 0;
+        let mut start = 0;
+        while start < bytes.len() {
+            let mut other = start + utf8_len(bytes[start]);
+            while other < bytes.len() {
+                assert!(
+                    !same_char(bytes, start, other),
+                    "A code page can't have a character twice"
+                );
+                other += utf8_len(bytes[other]);
+            }
+            count += 1;
+            start += utf8_len(bytes[start]);
+        }
+        assert!(
+            count == 256,
+            "A code page should have exactly 256 characters"
+        );
+
+        CodePage { name, characters }
+    }
+
+    /// The length of `code` encoded in this code page, or `None` if some character has
+    /// no byte in the code page
+    pub fn encoded_len(&self, code: &str) -> Option<usize> {
+        code.chars()
+            .map(|c| self.characters.contains(c).then_some(1))
+            .sum()
+    }
+}
+
+pub const VYXAL: CodePage = CodePage::new(
+    "Vyxal",
+    concat!(
+        "λƛ¬∧⟑∨⟇÷×«\n»°•ß†€½∆ø↔¢⌐æʀʁɾɽÞƈ∞¨",
+        " !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]`^_abcdefghijklmnopqrstuvwxyz{|}~",
+        "↑↓∴∵›‹∷¤ð→←βτȧḃċḋėḟġḣḭŀṁṅȯṗṙṡṫẇẋẏż√⟨⟩‛₀₁₂₃₄₅₆₇₈¶⁋§ε¡∑¦≈µȦḂĊḊĖḞĠḢİĿṀṄȮṖṘṠṪẆẊẎŻ₌₍⁰¹²∇⌈⌊¯±₴…□↳↲⋏⋎꘍ꜝ℅≤≥≠⁼ƒɖ∪∩⊍£¥⇧⇩ǍǎǏǐǑǒǓǔ⁽‡≬⁺↵⅛¼¾Π„‟"
+    ),
+);
+
+/// Counts the bytes in a solution. This is used for scoring, so the editor byte counter
+/// mirrors it in `js/code_editing/code_editor.ts`.
+///
+/// Browsers submit line breaks as "\r\n", each of those counts as a single "\n". If the
+/// language has a code page and every character is in it, the code is counted in that
+/// code page, otherwise it is counted as UTF-8.
+pub fn byte_count(code: &str, code_page: Option<&CodePage>) -> usize {
+    let carriage_returns = code.matches("\r\n").count();
+
+    code_page
+        .and_then(|code_page| code_page.encoded_len(&code.replace("\r\n", "\n")))
+        .unwrap_or_else(|| code.len() - carriage_returns)
+}
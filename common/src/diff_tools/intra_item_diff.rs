@@ -0,0 +1,79 @@
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use similar::{ChangeTag, TextDiff};
+
+/// Diffing characters is quadratic in the worst case, longer items are diffed by words
+const MAX_CHAR_DIFF_LENGTH: usize = 1000;
+/// Below this, the items have so little in common that highlighting everything but a few
+/// characters is more confusing than helpful
+const MIN_SIMILARITY: f32 = 0.5;
+/// Items are diffed one pair at a time, so each gets only a small part of the time budget
+const ITEM_DIFF_TIME_BUDGET: Duration = Duration::from_millis(5);
+
+/// A part of the content of a [`super::Field`], the changed parts are highlighted
+#[derive(Serialize, PartialEq, Eq, Clone, Debug)]
+pub struct FieldSpan {
+    pub content: String,
+    pub changed: bool,
+}
+
+/// Whitespace is invisible, so when it is what changed it is shown with glyphs instead
+fn show_whitespace(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            ' ' => '·',
+            '\t' => '→',
+            '\r' => '␍',
+            '\n' => '↵',
+            c if c.is_whitespace() => '␣',
+            c => c,
+        })
+        .collect()
+}
+
+/// Adds to the last span if it is changed the same way, whitespace in changed spans is shown
+/// with glyphs
+pub fn push_span(spans: &mut Vec<FieldSpan>, value: &str, changed: bool) {
+    let content = if changed {
+        show_whitespace(value)
+    } else {
+        value.to_owned()
+    };
+
+    match spans.last_mut() {
+        Some(last) if last.changed == changed => last.content.push_str(&content),
+        _ => spans.push(FieldSpan { content, changed }),
+    }
+}
+
+/// Finds what changed inside two items that were matched up as different, as spans for the
+/// old and the new item. Returns `None` if the items are too different to be worth it.
+pub fn intra_item_diff(old: &str, new: &str) -> Option<(Vec<FieldSpan>, Vec<FieldSpan>)> {
+    let mut config = TextDiff::configure();
+    config.deadline(Instant::now() + ITEM_DIFF_TIME_BUDGET);
+    let diff = if old.len().max(new.len()) <= MAX_CHAR_DIFF_LENGTH {
+        config.diff_chars(old, new)
+    } else {
+        config.diff_words(old, new)
+    };
+
+    if diff.ratio() < MIN_SIMILARITY {
+        return None;
+    }
+
+    let mut old_spans = vec![];
+    let mut new_spans = vec![];
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Equal => {
+                push_span(&mut old_spans, change.value(), false);
+                push_span(&mut new_spans, change.value(), false);
+            }
+            ChangeTag::Delete => push_span(&mut old_spans, change.value(), true),
+            ChangeTag::Insert => push_span(&mut new_spans, change.value(), true),
+        }
+    }
+
+    Some((old_spans, new_spans))
+}
@@ -1,13 +1,23 @@
 mod filter_iterator_but_keep_context;
+mod intra_item_diff;
+mod numeric_diff;
 mod raw_itemwise_diff;
+mod unordered_diff;
 
-use std::sync::LazyLock;
+use std::{borrow::Cow, sync::LazyLock};
 
 pub use filter_iterator_but_keep_context::FilterIteratorButKeepContext;
+pub use intra_item_diff::{FieldSpan, intra_item_diff, push_span};
 use itertools::Itertools;
+pub use numeric_diff::{get_numeric_diff_elements, numbers_close};
 use raw_itemwise_diff::{DoubleDiffKind, RawDoubleDiffElement, RawItemwiseDiff};
 use serde::Serialize;
 use similar::{ChangeTag, TextDiff, TextDiffConfig};
+pub use unordered_diff::get_unordered_diff_elements;
+
+/// Only this many pairs of different items get what changed inside them highlighted, the
+/// rest is shown as is
+const MAX_INTRA_ITEM_DIFFS: usize = 100;
 
 pub fn get_diff_elements(left: &str, right: &str, sep: &str, start_column: usize) -> Columns {
     let iterator = FilterIteratorButKeepContext::new(
@@ -18,6 +28,7 @@ pub fn get_diff_elements(left: &str, right: &str, sep: &str, start_column: usize
     );
 
     let mut height = 0;
+    let mut intra_item_diffs = 0;
 
     let fields = iterator.flat_map(|item| match item.kind {
         DoubleDiffKind::Identical(item) => {
@@ -30,6 +41,7 @@ pub fn get_diff_elements(left: &str, right: &str, sep: &str, start_column: usize
                         span: 1,
                         content: item.clone(),
                         row_span: 1,
+                        spans: vec![],
                     },
                     Field {
                         kind: FieldKind::Identical,
@@ -37,6 +49,7 @@ pub fn get_diff_elements(left: &str, right: &str, sep: &str, start_column: usize
                         span: 1,
                         content: item,
                         row_span: 1,
+                        spans: vec![],
                     },
                 ]
                 .into_iter(),
@@ -44,31 +57,45 @@ pub fn get_diff_elements(left: &str, right: &str, sep: &str, start_column: usize
         }
         DoubleDiffKind::Different(left, right) => {
             height += left.len().max(right.len());
-
-            Box::new(left.into_iter().zip_longest(right).flat_map(|pair| {
-                let pair_slice = match pair {
-                    itertools::EitherOrBoth::Both(a, b) => [Some(a), Some(b)],
-                    itertools::EitherOrBoth::Left(a) => [Some(a), None],
-                    itertools::EitherOrBoth::Right(b) => [None, Some(b)],
-                };
-
-                pair_slice
-                    .into_iter()
-                    .enumerate()
-                    .flat_map(|(index, content)| {
-                        content.map(|content| Field {
-                            kind: match index {
-                                0 => FieldKind::Delete,
-                                1 => FieldKind::Insert,
-                                _ => unreachable!(),
-                            },
-                            column: index + start_column,
-                            span: 1,
-                            content,
-                            row_span: 1,
+            let allowed_intra_item_diffs = MAX_INTRA_ITEM_DIFFS.saturating_sub(intra_item_diffs);
+            intra_item_diffs += left.len().min(right.len());
+
+            Box::new(left.into_iter().zip_longest(right).enumerate().flat_map(
+                move |(pair_index, pair)| {
+                    let pair_slice = match pair {
+                        itertools::EitherOrBoth::Both(a, b) => {
+                            let (a_spans, b_spans) = (pair_index < allowed_intra_item_diffs)
+                                .then(|| intra_item_diff(&a, &b))
+                                .flatten()
+                                .unzip();
+                            [
+                                Some((a, a_spans.unwrap_or_default())),
+                                Some((b, b_spans.unwrap_or_default())),
+                            ]
+                        }
+                        itertools::EitherOrBoth::Left(a) => [Some((a, vec![])), None],
+                        itertools::EitherOrBoth::Right(b) => [None, Some((b, vec![]))],
+                    };
+
+                    pair_slice
+                        .into_iter()
+                        .enumerate()
+                        .flat_map(move |(index, content)| {
+                            content.map(|(content, spans)| Field {
+                                kind: match index {
+                                    0 => FieldKind::Delete,
+                                    1 => FieldKind::Insert,
+                                    _ => unreachable!(),
+                                },
+                                column: index + start_column,
+                                span: 1,
+                                content,
+                                row_span: 1,
+                                spans,
+                            })
                         })
-                    })
-            }))
+                },
+            ))
         }
         DoubleDiffKind::Skipped(number) => {
             height += 1;
@@ -79,6 +106,7 @@ pub fn get_diff_elements(left: &str, right: &str, sep: &str, start_column: usize
                     span: 2,
                     content: format!("{} identical lines skipped", number),
                     row_span: 1,
+                    spans: vec![],
                 }]
                 .into_iter(),
             )
@@ -87,7 +115,7 @@ pub fn get_diff_elements(left: &str, right: &str, sep: &str, start_column: usize
 
     Columns {
         fields: fields.collect(),
-        column_titles: vec![Some("Output"), Some("Expected")],
+        column_titles: vec![Some("Output".into()), Some("Expected".into())],
         height,
     }
 }
@@ -133,6 +161,9 @@ pub struct Field {
     pub row_span: usize,
     pub content: String,
     pub kind: FieldKind,
+    /// What changed inside the content, empty if there is nothing to highlight
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub spans: Vec<FieldSpan>,
 }
 
 #[derive(Serialize, PartialEq, Eq, Clone, Copy)]
@@ -142,11 +173,13 @@ pub enum FieldKind {
     Delete,
     Identical,
     Meta,
+    /// The content is a `data:` URL of an image
+    Image,
 }
 
 #[derive(Serialize, PartialEq, Eq, Clone)]
 pub struct Columns {
-    pub column_titles: Vec<Option<&'static str>>,
+    pub column_titles: Vec<Option<Cow<'static, str>>>,
     pub fields: Vec<Field>,
     pub height: usize,
 }
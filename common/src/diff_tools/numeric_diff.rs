@@ -0,0 +1,165 @@
+use itertools::Itertools;
+
+use super::{
+    Columns, Field, FieldKind, FilterIteratorButKeepContext, MAX_INTRA_ITEM_DIFFS, intra_item_diff,
+};
+
+/// Parses a plain decimal number with an optional exponent, like `-1.5e3`. Forms that Rust
+/// accepts but JavaScript's `Number` doesn't, or the other way around (`inf`, `NaN`, `0x10`),
+/// aren't numbers, so the diff agrees with `numbersClose` in `runner-lib.ts`.
+fn parse_number(word: &str) -> Option<f64> {
+    let unsigned = word.strip_prefix(['+', '-']).unwrap_or(word);
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (unsigned, None),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+
+    let valid_mantissa =
+        !(integer.is_empty() && fraction.is_empty()) && all_digits(integer) && all_digits(fraction);
+    let valid_exponent = exponent.is_none_or(|exponent| {
+        let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        !digits.is_empty() && all_digits(digits)
+    });
+
+    if valid_mantissa && valid_exponent {
+        word.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Compares the items word by word, words that are both numbers are equal if they differ by at
+/// most `tolerance`, relative to the right number if that is larger than 1
+pub fn numbers_close(left: &str, right: &str, tolerance: f64) -> bool {
+    left.split_whitespace()
+        .zip_longest(right.split_whitespace())
+        .all(|pair| match pair {
+            itertools::EitherOrBoth::Both(left, right) => {
+                left == right
+                    || matches!(
+                        (parse_number(left), parse_number(right)),
+                        (Some(left), Some(right))
+                            if (left - right).abs() <= tolerance * right.abs().max(1.0)
+                    )
+            }
+            _ => false,
+        })
+}
+
+/// Compares the items in order, numbers within the tolerance count as identical. See
+/// [`numbers_close`].
+pub fn get_numeric_diff_elements(
+    left: &str,
+    right: &str,
+    sep: &str,
+    tolerance: f64,
+    start_column: usize,
+) -> Columns {
+    let mut intra_item_diffs = 0;
+    let rows = left
+        .trim_end()
+        .split(sep)
+        .zip_longest(right.trim_end().split(sep))
+        .map(|pair| {
+            let (left, right) = pair.or_default();
+            let (left, right) = (left.trim_end(), right.trim_end());
+            let close = numbers_close(left, right, tolerance);
+            let (left_spans, right_spans) = if close || intra_item_diffs >= MAX_INTRA_ITEM_DIFFS {
+                (vec![], vec![])
+            } else {
+                intra_item_diffs += 1;
+                intra_item_diff(left, right).unwrap_or_default()
+            };
+
+            [
+                (left, left_spans, FieldKind::Delete),
+                (right, right_spans, FieldKind::Insert),
+            ]
+            .into_iter()
+            .enumerate()
+            .map(|(index, (content, spans, kind))| Field {
+                kind: if close { FieldKind::Identical } else { kind },
+                column: start_column + index,
+                span: 1,
+                content: content.to_owned(),
+                row_span: 1,
+                spans,
+            })
+            .collect::<Vec<_>>()
+        });
+
+    let fields: Vec<Vec<Field>> = FilterIteratorButKeepContext::new(
+        rows,
+        |row| row[0].kind != FieldKind::Identical,
+        |rows_skipped| {
+            vec![Field {
+                kind: FieldKind::Meta,
+                column: start_column,
+                span: 2,
+                content: format!("{rows_skipped} matching lines skipped"),
+                row_span: 1,
+                spans: vec![],
+            }]
+        },
+        1,
+    )
+    .collect();
+
+    Columns {
+        height: fields.len(),
+        fields: fields.into_iter().flatten().collect(),
+        column_titles: vec![Some("Output".into()), Some("Expected".into())],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_decimals_with_exponents() {
+        for (word, number) in [
+            ("0", 0.0),
+            ("-12", -12.0),
+            ("+3", 3.0),
+            ("1.5", 1.5),
+            ("1.", 1.0),
+            (".5", 0.5),
+            ("-.5", -0.5),
+            ("1e3", 1000.0),
+            ("1.e3", 1000.0),
+            ("2.5E-2", 0.025),
+            ("1e+2", 100.0),
+        ] {
+            assert_eq!(parse_number(word), Some(number), "{word}");
+        }
+    }
+
+    #[test]
+    fn rejects_forms_outside_the_grammar() {
+        for word in [
+            "", "+", "-", ".", "e3", ".e3", "1e", "1e+", "1.5.2", "--1", "+-1", "0x10", "0b1",
+            "0o7", "1_000", "inf", "-inf", "Infinity", "NaN", "nan", "1 2", " 1", "1f", "١",
+        ] {
+            assert_eq!(parse_number(word), None, "{word:?}");
+        }
+    }
+
+    #[test]
+    fn numbers_within_the_tolerance_are_close() {
+        assert!(numbers_close("1.0000001 2", "1 2", 1e-6));
+        assert!(numbers_close("1000.0005", "1000", 1e-6));
+        assert!(!numbers_close("1.1", "1", 1e-6));
+        assert!(!numbers_close("1 2", "1", 1e-6));
+    }
+
+    #[test]
+    fn only_identical_words_match_outside_the_grammar() {
+        assert!(numbers_close("Infinity", "Infinity", 1e-6));
+        assert!(!numbers_close("inf", "Infinity", 1e-6));
+        assert!(!numbers_close("0x10", "16", 1e-6));
+        assert!(!numbers_close("NaN", "nan", 1e-6));
+    }
+}
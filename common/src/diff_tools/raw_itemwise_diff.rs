@@ -1,6 +1,15 @@
-use std::collections::VecDeque;
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
 
-use similar::{DiffOp, capture_diff};
+use similar::{DiffOp, capture_diff_deadline};
+
+/// Myers is quadratic in the worst case, above this many items the outputs are compared
+/// item by item instead
+const MAX_MYERS_ITEMS: usize = 10_000;
+/// After this, Myers gives up on finding the smallest diff and returns a coarser one
+const DIFF_TIME_BUDGET: Duration = Duration::from_millis(50);
 
 pub(super) enum DoubleDiffKind {
     Identical(String),
@@ -52,13 +61,18 @@ impl RawItemwiseDiff {
             .map(strip_string)
             .collect::<Vec<_>>();
 
-        let diff = capture_diff(
-            similar::Algorithm::Myers,
-            &left_split,
-            0..left_split.len(),
-            &right_split,
-            0..right_split.len(),
-        )
+        let diff = if left_split.len() + right_split.len() > MAX_MYERS_ITEMS {
+            positional_diff(&left_split, &right_split)
+        } else {
+            capture_diff_deadline(
+                similar::Algorithm::Myers,
+                &left_split,
+                0..left_split.len(),
+                &right_split,
+                0..right_split.len(),
+                Some(Instant::now() + DIFF_TIME_BUDGET),
+            )
+        }
         .into();
 
         RawItemwiseDiff {
@@ -70,6 +84,55 @@ impl RawItemwiseDiff {
     }
 }
 
+/// Compares the items at the same positions, runs of equal and different items are grouped.
+/// Linear, but an inserted or removed item makes everything after it different.
+fn positional_diff(left: &[String], right: &[String]) -> Vec<DiffOp> {
+    let common = left.len().min(right.len());
+    let mut diff = vec![];
+    let mut start = 0;
+
+    while start < common {
+        let equal = left[start] == right[start];
+        let len = left[start..common]
+            .iter()
+            .zip(&right[start..common])
+            .take_while(|(a, b)| (a == b) == equal)
+            .count();
+
+        diff.push(if equal {
+            DiffOp::Equal {
+                old_index: start,
+                new_index: start,
+                len,
+            }
+        } else {
+            DiffOp::Replace {
+                old_index: start,
+                old_len: len,
+                new_index: start,
+                new_len: len,
+            }
+        });
+        start += len;
+    }
+
+    if left.len() > common {
+        diff.push(DiffOp::Delete {
+            old_index: common,
+            old_len: left.len() - common,
+            new_index: common,
+        });
+    } else if right.len() > common {
+        diff.push(DiffOp::Insert {
+            old_index: common,
+            new_index: common,
+            new_len: right.len() - common,
+        });
+    }
+
+    diff
+}
+
 impl Iterator for RawItemwiseDiff {
     type Item = RawDoubleDiffElement;
 
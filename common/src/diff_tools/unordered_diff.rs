@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use super::{Columns, Field, FieldKind};
+
+fn split_items<'a>(text: &'a str, sep: &str) -> Vec<&'a str> {
+    match text.trim_end() {
+        "" => vec![],
+        text => text.split(sep).map(str::trim_end).collect(),
+    }
+}
+
+/// Compares the items as multisets, so the order doesn't matter. Matching items are only
+/// counted, items that are in one side but not the other are shown next to each other.
+pub fn get_unordered_diff_elements(
+    left: &str,
+    right: &str,
+    sep: &str,
+    start_column: usize,
+) -> Columns {
+    let left_items = split_items(left, sep);
+    let right_items = split_items(right, sep);
+
+    let mut unmatched_right: HashMap<&str, usize> = right_items.iter().copied().counts();
+    let mut extra = vec![];
+    let mut matched = 0;
+    for &item in &left_items {
+        match unmatched_right.get_mut(item) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                matched += 1;
+            }
+            _ => extra.push(item),
+        }
+    }
+
+    // Missing items keep the order of the right side
+    let missing: Vec<&str> = right_items
+        .iter()
+        .copied()
+        .filter(|item| match unmatched_right.get_mut(item) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        })
+        .collect();
+
+    let mut fields = vec![];
+    let mut height = 0;
+    if matched > 0 {
+        fields.push(Field {
+            kind: FieldKind::Meta,
+            column: start_column,
+            span: 2,
+            content: format!("{matched} matching items, in any order"),
+            row_span: 1,
+            spans: vec![],
+        });
+        height += 1;
+    }
+
+    for pair in extra.into_iter().zip_longest(missing) {
+        let (left, right) = pair.left_and_right();
+        fields.extend(
+            [(left, FieldKind::Delete), (right, FieldKind::Insert)]
+                .into_iter()
+                .enumerate()
+                .flat_map(|(index, (content, kind))| {
+                    content.map(|content| Field {
+                        kind,
+                        column: start_column + index,
+                        span: 1,
+                        content: content.to_owned(),
+                        row_span: 1,
+                        spans: vec![],
+                    })
+                }),
+        );
+        height += 1;
+    }
+
+    Columns {
+        fields,
+        column_titles: vec![Some("Output".into()), Some("Expected".into())],
+        height,
+    }
+}
@@ -3,7 +3,10 @@ use std::time::Duration;
 use phf::phf_map;
 use serde::Serialize;
 
-use crate::{DEFAULT_TIMERS, Timers};
+use crate::{
+    DEFAULT_TIMERS, Timers,
+    code_page::{self, CodePage},
+};
 
 #[derive(Serialize, Default)]
 #[serde(rename_all(serialize = "camelCase"))]
@@ -22,7 +25,18 @@ pub struct Lang {
     pub extra_runtime: Timers,
     pub color: &'static str,
     pub placeholder_text: &'static str,
+    /// Prints "Hello, World!" followed by whatever it reads on stdin, used by the runner self test
+    #[serde(skip)]
+    pub self_test_program: &'static str,
     pub syntax_name: &'static str,
+    /// Single byte character set used for scoring instead of UTF-8, if the language has one
+    pub code_page: Option<&'static CodePage>,
+}
+
+impl Lang {
+    pub fn byte_count(&self, code: &str) -> usize {
+        code_page::byte_count(code, self.code_page)
+    }
 }
 
 pub const DEFAULT_LANG: Lang = Lang {
@@ -44,7 +58,9 @@ pub const DEFAULT_LANG: Lang = Lang {
     },
     color: "transparent",
     placeholder_text: "",
+    self_test_program: "",
     syntax_name: "txt",
+    code_page: None,
 };
 
 pub const LANGS: phf::Map<&'static str, Lang> = phf_map! {
@@ -58,6 +74,7 @@ pub const LANGS: phf::Map<&'static str, Lang> = phf_map! {
         color: "#f1e05a",
         placeholder_text: include_str!("./placeholders/nodejs"),
         syntax_name: "js",
+        self_test_program: include_str!("./self_tests/nodejs"),
         ..DEFAULT_LANG
     },
     "deno" => Lang {
@@ -74,6 +91,7 @@ pub const LANGS: phf::Map<&'static str, Lang> = phf_map! {
         color: "white",
         placeholder_text: include_str!("./placeholders/nodejs"),
         syntax_name: "js",
+        self_test_program: include_str!("./self_tests/nodejs"),
         ..DEFAULT_LANG
     },
     "python" => Lang {
@@ -87,6 +105,7 @@ pub const LANGS: phf::Map<&'static str, Lang> = phf_map! {
         color:  "#3572A5",
         placeholder_text: include_str!("./placeholders/python"),
         syntax_name: "py",
+        self_test_program: include_str!("./self_tests/python"),
         ..DEFAULT_LANG
     },
     "rust" => Lang {
@@ -114,6 +133,7 @@ pub const LANGS: phf::Map<&'static str, Lang> = phf_map! {
         ],
         placeholder_text: include_str!("./placeholders/rust"),
         syntax_name: "rs",
+        self_test_program: include_str!("./self_tests/rust"),
         ..DEFAULT_LANG
     },
     "vyxal" => Lang {
@@ -125,6 +145,8 @@ pub const LANGS: phf::Map<&'static str, Lang> = phf_map! {
         icon: "vyxal.svg",
         extra_runtime: Timers { run: Duration::from_secs(2), ..DEFAULT_TIMERS },
         color: "#3498db",
+        self_test_program: include_str!("./self_tests/vyxal"),
+        code_page: Some(&code_page::VYXAL),
         ..DEFAULT_LANG
     },
     "vyxal3" => Lang {
@@ -146,6 +168,7 @@ pub const LANGS: phf::Map<&'static str, Lang> = phf_map! {
             ..DEFAULT_TIMERS
         },
         color: "#3498db",
+        self_test_program: include_str!("./self_tests/vyxal3"),
         ..DEFAULT_LANG
     },
     "tinyapl" => Lang {
@@ -156,6 +179,7 @@ pub const LANGS: phf::Map<&'static str, Lang> = phf_map! {
         latest_version: "0.12.0.0",
         icon: "tinyapl.svg",
         color: "#94e044",
+        self_test_program: include_str!("./self_tests/tinyapl"),
         ..DEFAULT_LANG
     },
     "tcc" => Lang {
@@ -175,6 +199,7 @@ pub const LANGS: phf::Map<&'static str, Lang> = phf_map! {
             ("/usr/include", "/usr/include")
         ],
         syntax_name: "c",
+        self_test_program: include_str!("./self_tests/c"),
         ..DEFAULT_LANG
     },
     "kotlin" => Lang {
@@ -207,6 +232,7 @@ pub const LANGS: phf::Map<&'static str, Lang> = phf_map! {
             ..DEFAULT_TIMERS
         },
         placeholder_text: include_str!("./placeholders/kotlin"),
+        self_test_program: include_str!("./self_tests/kotlin"),
         ..DEFAULT_LANG
     }
 };
@@ -1,15 +1,19 @@
 pub mod achievements;
+pub mod binary;
+pub mod code_page;
 #[cfg(feature = "diff")]
 pub mod diff_tools;
 pub mod langs;
 pub mod slug;
 pub mod sql_enums;
+#[cfg(feature = "telemetry")]
+pub mod telemetry;
 pub mod urls;
 
-use std::time::Duration;
+use std::{collections::BTreeMap, time::Duration};
 
 pub use achievements::{AchievementCategory, AchievementType};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, de::Error as _};
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -17,8 +21,14 @@ pub struct JudgeResult {
     pub pass: bool,
     pub test_cases: Vec<TestCase>,
     pub points: Option<i32>,
+    #[serde(default)]
+    pub metrics: Metrics,
 }
 
+/// Named secondary scores of a solution, such as the runtime or a metric reported by the
+/// judge. Challenges can use one to break ties between solutions with equal points.
+pub type Metrics = BTreeMap<String, f64>;
+
 #[derive(Clone, Copy)]
 pub enum TimerType {
     Run,
@@ -55,6 +65,103 @@ impl Timers {
             TimerType::Judge => &self.judge,
         }
     }
+
+    pub fn total(&self) -> Duration {
+        self.run + self.compile + self.judge
+    }
+}
+
+/// Limits for judging a solution that a challenge can raise, up to [`RunLimits::MAX`]
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct RunLimits {
+    /// Seconds all runs of the solution can take together, languages can add extra time
+    #[serde(deserialize_with = "deserialize_form_number")]
+    pub run_timeout: u32,
+    /// Seconds the judge itself can take, outside of runs
+    #[serde(deserialize_with = "deserialize_form_number")]
+    pub judge_timeout: u32,
+    #[serde(deserialize_with = "deserialize_form_number")]
+    pub max_test_cases: u32,
+    /// Bytes of input the judge can give to a single run
+    #[serde(deserialize_with = "deserialize_form_number")]
+    pub max_input_size: u32,
+}
+
+/// Forms submit numbers as strings
+fn deserialize_form_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum FormNumber {
+        Number(u32),
+        Text(String),
+    }
+
+    match FormNumber::deserialize(deserializer)? {
+        FormNumber::Number(number) => Ok(number),
+        FormNumber::Text(text) => text
+            .trim()
+            .parse()
+            .map_err(|_| D::Error::custom("Expected a positive number")),
+    }
+}
+
+impl RunLimits {
+    pub const DEFAULT: RunLimits = RunLimits {
+        run_timeout: 3,
+        judge_timeout: 1,
+        max_test_cases: 50,
+        max_input_size: 64 * 1024,
+    };
+
+    /// The bounds admins agreed to, so one challenge can't hog the lang runner
+    pub const MAX: RunLimits = RunLimits {
+        run_timeout: 10,
+        judge_timeout: 5,
+        max_test_cases: 200,
+        max_input_size: 1024 * 1024,
+    };
+
+    /// Compile time every language gets, on top of its extra runtime
+    pub const COMPILE_TIMEOUT: Duration = Duration::from_secs(1);
+
+    /// The time the lang runner gives a judge, `extra_runtime` is the one of the language
+    pub fn timers(&self, extra_runtime: &Timers) -> Timers {
+        Timers {
+            judge: Duration::from_secs(self.judge_timeout.into()) + extra_runtime.judge,
+            run: Duration::from_secs(self.run_timeout.into()) + extra_runtime.run,
+            compile: Self::COMPILE_TIMEOUT + extra_runtime.compile,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        let RunLimits {
+            run_timeout,
+            judge_timeout,
+            max_test_cases,
+            max_input_size,
+        } = *self;
+
+        if !(1..=Self::MAX.run_timeout).contains(&run_timeout) {
+            return Err("The run timeout should be between 1 and 10 seconds");
+        }
+        if !(1..=Self::MAX.judge_timeout).contains(&judge_timeout) {
+            return Err("The judge timeout should be between 1 and 5 seconds");
+        }
+        if !(1..=Self::MAX.max_test_cases).contains(&max_test_cases) {
+            return Err("The maximum number of test cases should be between 1 and 200");
+        }
+        if !(1..=Self::MAX.max_input_size).contains(&max_input_size) {
+            return Err("The maximum input size should be between 1 byte and 1 MiB");
+        }
+        Ok(())
+    }
+}
+
+impl Default for RunLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -67,6 +174,70 @@ pub struct RunLangOutput {
     pub timers: Timers,
 }
 
+/// The outcome of running a language's self test program on the lang runner
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestResult {
+    pub language: String,
+    pub version: String,
+    pub pass: bool,
+    pub timed_out: bool,
+    pub runtime: f32,
+    pub timers: Timers,
+    pub stdout: String,
+    pub stderr: String,
+    /// Set if the language could not be installed or the judge itself failed
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledLanguageVersion {
+    pub language: String,
+    pub version: String,
+    /// Size of the install folder in bytes
+    pub disk_usage: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageVersion {
+    pub language: String,
+    pub version: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UninstallResult {
+    pub language: String,
+    pub version: String,
+    pub freed_bytes: u64,
+    pub error: Option<String>,
+}
+
+/// A type error that `deno check` found in a judge
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct JudgeDiagnostic {
+    /// The TypeScript error code, like `TS2304`
+    pub code: String,
+    pub message: String,
+    /// 1-based and relative to the judge, `None` if the error is outside of the judge
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl std::fmt::Display for JudgeDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "Line {line}, column {column}: ")?,
+            (Some(line), None) => write!(f, "Line {line}: ")?,
+            _ => {}
+        }
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub enum TestPassState {
     /// The test passed
@@ -85,6 +256,9 @@ pub enum TestPassState {
 pub struct TestCase {
     #[serde(default)]
     pub name: Option<String>,
+    /// Test cases with the same group are shown together in a collapsible section
+    #[serde(default)]
+    pub group: Option<String>,
     pub pass: TestPassState,
     pub result_display: ResultDisplay,
 }
@@ -106,6 +280,19 @@ pub enum DisplayMode {
     Normal,
     Filter,
     Test,
+    /// Compares the output as a grid of characters, for ASCII art
+    Grid,
+    /// The items can be in any order, only missing and extra items are shown
+    Unordered,
+    /// Numbers that differ by at most the tolerance count as identical
+    Numeric {
+        #[serde(default = "create_default_tolerance")]
+        tolerance: f64,
+    },
+}
+
+fn create_default_tolerance() -> f64 {
+    1e-6
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -129,9 +316,73 @@ pub enum ResultDisplay {
         input: Option<String>,
         output: String,
         error: String,
+        /// Missing for runs judged before exits were reported
+        #[serde(default)]
+        exit: Option<RunExit>,
+    },
+    /// Cells that line up in a grid, like ASCII art
+    Table {
+        #[serde(default)]
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    /// Byte outputs as hexdumps, next to each other if there is an expected output. Uses the
+    /// encoding of [`binary::to_binary_string`]
+    Hexdump {
+        output: String,
+        #[serde(default)]
+        expected: Option<String>,
+    },
+    /// A small PBM, PGM or PPM image, in the encoding of [`binary::to_binary_string`]
+    Image {
+        output: String,
+        #[serde(default)]
+        expected: Option<String>,
     },
 }
 
+/// Tables are rendered as a grid, so wider ones wouldn't fit on the page
+pub const MAX_TABLE_COLUMNS: usize = 8;
+pub const MAX_TABLE_ROWS: usize = 200;
+
+/// How the program of a run ended
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RunExit {
+    /// `None` when the program was killed by a signal
+    pub code: Option<i32>,
+    pub signal: Option<String>,
+    /// The run took longer than the timeout the judge gave it
+    pub timed_out: bool,
+    /// The program wrote more output than the runner keeps
+    pub output_truncated: bool,
+}
+
+impl RunExit {
+    /// Anything other than exiting with code 0 within the limits
+    pub fn is_abnormal(&self) -> bool {
+        self.code != Some(0) || self.timed_out || self.output_truncated
+    }
+}
+
+impl std::fmt::Display for RunExit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = vec![];
+        if self.timed_out {
+            parts.push("Timed out".to_owned());
+        }
+        match (&self.signal, self.code) {
+            (Some(signal), _) => parts.push(format!("Killed by signal {signal}")),
+            (None, Some(code)) => parts.push(format!("Exited with code {code}")),
+            (None, None) => {}
+        }
+        if self.output_truncated {
+            parts.push("Output was truncated".to_owned());
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
 impl ResultDisplay {
     pub fn truncate(&mut self, length: usize) {
         match self {
@@ -156,6 +407,7 @@ impl ResultDisplay {
                 input,
                 output,
                 error,
+                exit: _,
             } => {
                 if let Some(input) = input {
                     input.truncate(length);
@@ -163,6 +415,38 @@ impl ResultDisplay {
                 output.truncate(length);
                 error.truncate(length);
             }
+            ResultDisplay::Table { headers, rows } => {
+                headers.truncate(MAX_TABLE_COLUMNS);
+                rows.truncate(MAX_TABLE_ROWS);
+                for row in rows.iter_mut() {
+                    row.truncate(MAX_TABLE_COLUMNS);
+                }
+                for cell in headers.iter_mut().chain(rows.iter_mut().flatten()) {
+                    truncate_at_char(cell, length);
+                }
+            }
+            ResultDisplay::Hexdump { output, expected } => {
+                truncate_at_char(output, length);
+                if let Some(expected) = expected {
+                    truncate_at_char(expected, length);
+                }
+            }
+            // Half an image can't be shown, so too large images are dropped entirely
+            ResultDisplay::Image { output, expected } => {
+                if output.len() > length {
+                    output.clear();
+                }
+                if let Some(expected) = expected.as_mut()
+                    && expected.len() > length
+                {
+                    expected.clear();
+                }
+            }
         }
     }
 }
+
+/// Binary strings have chars that take two bytes, which [`String::truncate`] can't cut through
+fn truncate_at_char(text: &mut String, length: usize) {
+    text.truncate(text.floor_char_boundary(length));
+}
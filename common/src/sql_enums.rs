@@ -42,3 +42,82 @@ pub enum ChallengeDifficulty {
     Medium,
     Hard,
 }
+
+/// What the points of a challenge count when the judge does not return its own points
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(
+    feature = "sqlx",
+    sqlx(type_name = "scoring_unit", rename_all = "kebab-case")
+)]
+#[derive(Default)]
+pub enum ScoringUnit {
+    #[default]
+    Bytes,
+    /// Unicode scalar values
+    Characters,
+    Lines,
+    /// Runs of non whitespace characters with the same syntax highlighting scope
+    Tokens,
+    /// Scored by the judge, falls back to bytes if the judge does not return any points
+    Points,
+}
+
+/// Whether a lower or a higher number of points wins a challenge
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(
+    feature = "sqlx",
+    sqlx(type_name = "score_direction", rename_all = "kebab-case")
+)]
+#[derive(Default)]
+pub enum ScoreDirection {
+    #[default]
+    Minimize,
+    Maximize,
+}
+
+impl ScoreDirection {
+    /// Whether `points` is strictly better than `other`
+    pub fn is_better(self, points: i32, other: i32) -> bool {
+        match self {
+            ScoreDirection::Minimize => points < other,
+            ScoreDirection::Maximize => points > other,
+        }
+    }
+
+    /// Maps points so lower is always better, matches the `ranked_points` SQL function
+    pub fn ranked_points(self, points: i32) -> i32 {
+        match self {
+            ScoreDirection::Minimize => points,
+            ScoreDirection::Maximize => -points,
+        }
+    }
+
+    /// Maps a metric so lower is always better, matches the `ranked_metric` SQL function
+    pub fn ranked_metric(self, metric: f64) -> f64 {
+        match self {
+            ScoreDirection::Minimize => metric,
+            ScoreDirection::Maximize => -metric,
+        }
+    }
+}
+
+/// How the judge of a challenge is written
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Debug)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(
+    feature = "sqlx",
+    sqlx(type_name = "judge_format", rename_all = "kebab-case")
+)]
+#[derive(Default)]
+pub enum JudgeFormat {
+    /// An async generator function that gets a `Context` from `runner-lib.ts`
+    #[default]
+    Typescript,
+    /// Test cases and options in YAML or JSON, compiled to a TypeScript judge
+    Declarative,
+}
@@ -0,0 +1,90 @@
+use opentelemetry::{global, trace::TracerProvider as _};
+use opentelemetry_http::{HeaderExtractor, HeaderInjector};
+use opentelemetry_otlp::SpanExporter;
+use opentelemetry_sdk::{Resource, propagation::TraceContextPropagator, trace::SdkTracerProvider};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
+
+/// Header used to correlate a request to the main server with the runs it causes on the lang runner
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Keeps the OpenTelemetry exporter alive. Any spans that have not been sent yet
+/// are flushed when this is dropped, so hold on to it until the end of `main`.
+pub struct TelemetryGuard {
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take()
+            && let Err(e) = provider.shutdown()
+        {
+            eprintln!("Failed to shut down the OpenTelemetry exporter: {e:?}");
+        }
+    }
+}
+
+/// Installs the global tracing subscriber.
+///
+/// Logs always go to stderr, filtered by `RUST_LOG` (defaulting to `info`). If
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` or `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` is set spans are
+/// also exported over OTLP/HTTP, for example to a local Jaeger or OpenTelemetry collector.
+pub fn init_tracing(service_name: &'static str) -> TelemetryGuard {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let fmt_layer = tracing_subscriber::fmt::layer().with_writer(std::io::stderr);
+
+    let provider = otlp_endpoint_configured().then(|| build_provider(service_name));
+    let otel_layer = provider
+        .as_ref()
+        .map(|provider| tracing_opentelemetry::layer().with_tracer(provider.tracer(service_name)));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt_layer)
+        .with(otel_layer)
+        .init();
+
+    TelemetryGuard { provider }
+}
+
+fn otlp_endpoint_configured() -> bool {
+    [
+        "OTEL_EXPORTER_OTLP_ENDPOINT",
+        "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+    ]
+    .into_iter()
+    .any(|var| std::env::var(var).is_ok_and(|value| !value.is_empty()))
+}
+
+fn build_provider(service_name: &'static str) -> SdkTracerProvider {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .build()
+        .expect("Failed to create the OTLP span exporter");
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(service_name).build())
+        .build();
+    global::set_tracer_provider(provider.clone());
+    provider
+}
+
+/// Adds the `traceparent` header for the current span so the receiving service can
+/// continue the same trace
+pub fn inject_trace_context(headers: &mut http::HeaderMap) {
+    let context = tracing::Span::current().context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(headers))
+    });
+}
+
+/// Makes `span` a child of the trace described by the incoming `traceparent` header, if any
+pub fn set_parent_from_headers(span: &tracing::Span, headers: &http::HeaderMap) {
+    let context =
+        global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)));
+    // Fails only when no OpenTelemetry layer is installed, in which case there is nothing to link
+    let _ = span.set_parent(context);
+}
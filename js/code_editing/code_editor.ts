@@ -5,6 +5,7 @@ import { WorkerShape } from "@valtown/codemirror-ts/worker";
 import * as Comlink from "comlink";
 import { StateEffect } from "@codemirror/state";
 import { defaultPlugins } from "./code_mirror_default_plugins.ts";
+import { ScoringUnit } from "../types.ts";
 
 let typeScriptEnvironment: WorkerShape | undefined = undefined;
 
@@ -114,18 +115,53 @@ export function createCodemirrorFromTextAreas(): { [key: string]: EditorView } {
 }
 
 const textEncoder = new TextEncoder();
-export const lengthInBytes = (s: string): number =>
-  textEncoder.encode(s).length;
+/**
+ * Mirrors `byte_count` in `common/src/code_page.rs`, which is used for scoring
+ * @param codePage The characters of the language's code page, if it has one
+ */
+export const lengthInBytes = (s: string, codePage?: string): number => {
+  const normalized = s.replace(/\r\n/g, "\n");
+  const characters = [...normalized];
+  if (codePage && characters.every((c) => codePage.includes(c))) {
+    return characters.length;
+  }
+  return textEncoder.encode(normalized).length;
+};
+
+/**
+ * Mirrors `default_points` in `main-server/src/scoring.rs` for the units the browser can count.
+ * Tokens need the syntax definitions of the server and points come from the judge, so those
+ * are shown in bytes.
+ */
+export const lengthInUnit = (
+  s: string,
+  unit: ScoringUnit,
+  codePage?: string
+): number => {
+  const normalized = s.replace(/\r\n/g, "\n");
+  switch (unit) {
+    case "characters":
+      return [...normalized].length;
+    case "lines":
+      // Like Rust's `str::lines`, a trailing line break doesn't start a new line
+      return normalized === ""
+        ? 0
+        : normalized.replace(/\n$/, "").split("\n").length;
+    default:
+      return lengthInBytes(s, codePage);
+  }
+};
 
-export function onByteCountChange(
+export function onLengthChange(
   mainTextArea: EditorView,
-  e: (byte_count: number) => void
+  e: (length: number) => void,
+  length: (code: string) => number
 ) {
   mainTextArea.dispatch({
     effects: StateEffect.appendConfig.of([
       EditorView.updateListener.of((update) => {
         if (update.docChanged) {
-          e(lengthInBytes(mainTextArea.state.doc.toString()));
+          e(length(mainTextArea.state.doc.toString()));
         }
       }),
     ]),
@@ -1,6 +1,7 @@
 import { EditorView } from "codemirror";
 import { createCodemirrorFromTextAreas } from "./code_editing/code_editor";
 import { ResultDisplay, renderResultDisplay } from "./test_cases/test_case";
+import { JudgeFormat } from "./types.ts";
 
 window.addEventListener("DOMContentLoaded", () => {
   const textAreas = createCodemirrorFromTextAreas();
@@ -15,8 +16,23 @@ const setup_form = (
   form: HTMLFormElement,
   textAreas: { [name: string]: EditorView }
 ) => {
-  const { "challenge-judge": judgeTextArea, "example-code": exampleTextArea } =
-    textAreas;
+  const {
+    "challenge-judge": judgeTextArea,
+    "challenge-declarative-judge": declarativeJudgeTextArea,
+    "example-code": exampleTextArea,
+    "negative-examples": negativeExamplesTextArea,
+  } = textAreas;
+
+  const judgeFormatSelect = form.querySelector<HTMLSelectElement>(
+    "select#challenge-judge-format"
+  );
+  judgeFormatSelect?.addEventListener("change", () =>
+    switchJudgeFormat(
+      judgeFormatSelect,
+      judgeTextArea,
+      declarativeJudgeTextArea
+    )
+  );
 
   const submitButtons = form.querySelectorAll<HTMLButtonElement>(
     'button[type="submit"]'
@@ -34,7 +50,13 @@ const setup_form = (
     }
 
     jsonData["example_code"] = exampleTextArea.state.doc.toString();
-    jsonData["judge"] = judgeTextArea.state.doc.toString();
+    jsonData["negative_examples"] =
+      negativeExamplesTextArea.state.doc.toString();
+    jsonData["judge"] = (
+      judgeFormatSelect?.value === "declarative"
+        ? declarativeJudgeTextArea
+        : judgeTextArea
+    ).state.doc.toString();
 
     submitButtons.forEach((i) => (i.disabled = true));
 
@@ -72,13 +94,71 @@ const setup_form = (
   });
 };
 
+const switchJudgeFormat = async (
+  judgeFormatSelect: HTMLSelectElement,
+  judgeTextArea: EditorView,
+  declarativeJudgeTextArea: EditorView
+) => {
+  const declarative = judgeFormatSelect.value === "declarative";
+  document
+    .querySelector("#typescript-judge")
+    .classList.toggle("display-none", declarative);
+  document
+    .querySelector("#declarative-judge")
+    .classList.toggle("display-none", !declarative);
+
+  if (declarative) {
+    return;
+  }
+
+  // Start the TypeScript judge from the declarative one
+  const current = judgeTextArea.state.doc.toString();
+  if (
+    current.trim() !== "" &&
+    !confirm(
+      "Replace the TypeScript judge with the converted declarative judge?"
+    )
+  ) {
+    return;
+  }
+
+  let compiled: { judge?: string; error?: string };
+  try {
+    const response = await fetch("/challenge/compile-judge", {
+      method: "POST",
+      headers: {
+        "Content-Type": "application/json",
+        Accept: "application/json",
+      },
+      body: JSON.stringify({
+        judge: declarativeJudgeTextArea.state.doc.toString(),
+      }),
+    });
+    compiled = await response.json();
+  } catch (e) {
+    console.error(e);
+    return;
+  }
+
+  if (compiled.judge === undefined || compiled.judge === null) {
+    alert(`Could not convert the declarative judge: ${compiled.error}`);
+    return;
+  }
+
+  judgeTextArea.dispatch({
+    changes: { from: 0, to: current.length, insert: compiled.judge },
+  });
+};
+
 type submitChallengeResponse = {
   validation?: { [formField: string]: string } | undefined;
   tests?: ResultDisplay;
   description: string;
   judge: string;
+  judge_format: JudgeFormat;
   name: string;
   example_code: string;
+  negative_examples: string[];
   catgory: string;
   status: string;
   author_name?: string | undefined;
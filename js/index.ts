@@ -1,9 +1,11 @@
 import { EditorView } from "codemirror";
 import "./style.css";
 import { HeistsFilter } from "./heists-filtering";
+import { initMoreFieldsButtons } from "./test_cases/test_case";
 import "basecoat-css/all";
 
 // Initialize heists filtering when the DOM is loaded
 document.addEventListener("DOMContentLoaded", () => {
   new HeistsFilter();
+  initMoreFieldsButtons();
 });
@@ -3,13 +3,28 @@ import { renderResultDisplay, ResultDisplay } from "./test_cases/test_case";
 import { initTestCaseHideShow } from "./test_cases/test_case_show_hide";
 import {
   createCodemirrorFromTextAreas,
-  lengthInBytes,
-  onByteCountChange,
+  lengthInUnit,
+  onLengthChange,
 } from "./code_editing/code_editor";
-import { Challenge, LeaderboardEntry, Toast } from "./types.ts";
+import {
+  Challenge,
+  LeaderboardEntry,
+  ScoringUnit,
+  Toast,
+} from "./types.ts";
 
 let last_score: number | undefined;
 
+function improvement(toast: Toast, challenge: Challenge): number {
+  const difference = toast.new_scores.points - toast.old_scores.points;
+  return challenge.score_direction == "maximize" ? difference : -difference;
+}
+
+function improvedBy(toast: Toast, challenge: Challenge): string {
+  const verb = challenge.score_direction == "maximize" ? "Gained" : "Saved";
+  return `${verb} ${improvement(toast, challenge)} ${challenge.unit}`;
+}
+
 function displayToast(
   toast: Toast | undefined,
   status_code: number,
@@ -53,16 +68,14 @@ function displayToast(
     title = `${toast.new_scores.points} ${challenge.unit} (#${toast.new_scores.rank})`;
     description = `Earned ${toast.new_scores.score} score (#${toast.new_scores.rank} rank, ${toast.new_scores.points} ${challenge.unit})`;
   } else if (toast.new_scores.rank < toast.old_scores.rank) {
-    title = `Saved ${
-      toast.old_scores.points - toast.new_scores.points
-    } bytes (Rank #${toast.old_scores.rank} -> #${toast.new_scores.rank})`;
+    title = `${improvedBy(toast, challenge)} (Rank #${
+      toast.old_scores.rank
+    } -> #${toast.new_scores.rank})`;
     description = `+${toast.new_scores.score - last_score} score (#${
       toast.new_scores.rank
-    }, ${toast.new_scores.points} points)`;
-  } else if (toast.new_scores.points < toast.old_scores.points) {
-    title = `Saved ${toast.old_scores.points - toast.new_scores.points} ${
-      challenge.unit
-    }`;
+    }, ${toast.new_scores.points} ${challenge.unit})`;
+  } else if (improvement(toast, challenge) > 0) {
+    title = improvedBy(toast, challenge);
     description = `+${toast.new_scores.score - last_score} score (#${
       toast.new_scores.rank
     }, ${toast.new_scores.points} ${challenge.unit})`;
@@ -91,12 +104,14 @@ function displayToast(
   );
 }
 
-/// Only works from the solutions page
+/// Only works from the solutions page, test runs never save the solution
 async function submitNewSolution(
   mainTextArea: EditorView,
   submitButton: HTMLButtonElement,
   setOriginalText: (e: string) => void,
-  localStorageId: string
+  localStorageId: string,
+  url: string,
+  isTestRun: boolean
 ) {
   submitButton.disabled = true;
   try {
@@ -110,7 +125,7 @@ async function submitNewSolution(
       })
     );
 
-    const response = await fetch(window.location.href, {
+    const response = await fetch(url, {
       method: "POST",
       headers: {
         accept: "application/json",
@@ -139,9 +154,11 @@ async function submitNewSolution(
       };
     updateLeaderboard(leaderboard);
 
-    displayToast(toast, response.status, account_id, challenge);
+    if (!isTestRun) {
+      displayToast(toast, response.status, account_id, challenge);
+    }
 
-    if (tests.passed && response.status === 201) {
+    if (!isTestRun && tests.passed && response.status === 201) {
       setOriginalText(content);
     }
     const testsContainer = document.querySelector(
@@ -159,19 +176,26 @@ function setupJsSubmitOnForm(
   localStorageId: string,
   setOriginalText: (e: string) => void
 ) {
-  const form = document.querySelector("form.challenge-submission-form");
-  const submitButton = form.querySelector(
-    "button[type='submit']"
-  ) as HTMLButtonElement;
+  const form = document.querySelector<HTMLFormElement>(
+    "form.challenge-submission-form"
+  );
 
   form.addEventListener("submit", (ev) => {
     ev.preventDefault();
 
+    const submitButton =
+      ev.submitter instanceof HTMLButtonElement
+        ? ev.submitter
+        : form.querySelector<HTMLButtonElement>("button[type='submit']");
+    const isTestRun = submitButton.classList.contains("test-run-button");
+
     submitNewSolution(
       mainTextArea,
       submitButton,
       setOriginalText,
-      localStorageId
+      localStorageId,
+      isTestRun ? submitButton.formAction : window.location.href,
+      isTestRun
     );
   });
 }
@@ -210,6 +234,8 @@ function setupLeaderboardForm(form: HTMLFormElement) {
 
 function updateLeaderboard(ranking: LeaderboardEntry[]) {
   const leaderboard = document.querySelector(".leaderboard table tbody");
+  const tiebreaker = document.querySelector<HTMLTableElement>(".leaderboard table")
+    .dataset.tiebreaker;
 
   leaderboard.replaceChildren(
     ...ranking.map((entry: LeaderboardEntry) => {
@@ -236,6 +262,14 @@ function updateLeaderboard(ranking: LeaderboardEntry[]) {
       pointsCell.textContent = `${entry.points}`;
       row.appendChild(pointsCell);
 
+      if (tiebreaker) {
+        const tiebreakerCell = document.createElement("td");
+        const value = entry.metrics[tiebreaker];
+        tiebreakerCell.textContent =
+          value === undefined ? "" : `${Math.round(value * 1000) / 1000}`;
+        row.appendChild(tiebreakerCell);
+      }
+
       return row;
     })
   );
@@ -284,17 +318,25 @@ const setupEditorControls = (
 ) => {
   editorControls.classList.remove("hidden");
 
-  const byteCountElement = editorControls.querySelector("#byte-counter")!;
+  const byteCountElement =
+    editorControls.querySelector<HTMLElement>("#byte-counter")!;
+  const codePage = byteCountElement.dataset.codePage || undefined;
+  const unit = byteCountElement.dataset.unit as ScoringUnit;
+  const length = (code: string) => lengthInUnit(code, unit, codePage);
   const resetButton = editorControls.querySelector<HTMLButtonElement>(
     "#restore-solution-button"
   )!;
   const currentCode = mainTextArea.state.doc.toString();
 
-  byteCountElement.textContent = lengthInBytes(currentCode).toString();
+  byteCountElement.textContent = length(currentCode).toString();
 
-  onByteCountChange(mainTextArea, (byteCount) => {
-    byteCountElement.textContent = byteCount.toString();
-  });
+  onLengthChange(
+    mainTextArea,
+    (count) => {
+      byteCountElement.textContent = count.toString();
+    },
+    length
+  );
 
   if (currentCode === "") {
     resetButton.style.display = "none";
@@ -4,16 +4,44 @@ import { Challenge } from "../types.ts";
 export type ResultDisplay = {
   judgeError: null | string;
   passed: boolean;
-  tests: Test[];
+  groups: TestGroup[];
   timedOut: boolean;
   points: number | undefined;
 };
 
+type TestGroup = {
+  /** Test cases without a group aren't put in a collapsible section */
+  name: string | null;
+  tests: Test[];
+  counts: TestGroupCounts;
+  defaultOpen: boolean;
+};
+
+type TestGroupCounts = {
+  pass: number;
+  fail: number;
+  warning: number;
+  info: number;
+};
+
 type Test = {
   columns: Columns;
   status: string;
   title: string | null;
   defaultVisible: boolean;
+  moreFields?: MoreFields | null;
+};
+
+/** Set when a test case has too many fields to send at once */
+type MoreFields = {
+  id: string;
+  offset: number;
+  remaining: number;
+};
+
+type FieldPage = {
+  fields: Field[];
+  more: MoreFields | null;
 };
 
 type Columns = {
@@ -27,7 +55,13 @@ type Field = {
   span: number;
   row_span: number;
   content: string;
-  kind: "identical" | "insert" | "delete" | "meta";
+  kind: "identical" | "insert" | "delete" | "meta" | "image";
+  spans?: FieldSpan[] | undefined;
+};
+
+type FieldSpan = {
+  content: string;
+  changed: boolean;
 };
 
 function getOrCreateElement(
@@ -74,7 +108,58 @@ export function renderResultDisplay(
     judgeErrorsDiv.querySelector("pre").textContent = "";
   }
 
-  testCasesDiv.replaceChildren(...display.tests.map(renderTestCase));
+  testCasesDiv.replaceChildren(...display.groups.flatMap(renderTestGroup));
+}
+
+function renderTestGroup(group: TestGroup): HTMLElement[] {
+  const tests = group.tests.map(renderTestCase);
+  if (group.name === null) {
+    return tests;
+  }
+
+  const details = document.createElement("details");
+  details.classList.add("test-group");
+  details.classList.toggle("test-group-fail", group.counts.fail > 0);
+  details.open = group.defaultOpen;
+
+  const summary = document.createElement("summary");
+  summary.classList.add("test-group-summary");
+
+  const title = document.createElement("h2");
+  title.classList.add("test-group-title");
+  title.textContent = group.name;
+  summary.appendChild(title);
+  summary.appendChild(renderTestGroupBadges(group.counts));
+
+  details.appendChild(summary);
+  details.append(...tests);
+
+  return [details];
+}
+
+function renderTestGroupBadges(counts: TestGroupCounts): HTMLDivElement {
+  const badges = document.createElement("div");
+  badges.classList.add("test-group-badges");
+
+  const labels: [keyof TestGroupCounts, string][] = [
+    ["pass", `${counts.pass} passed`],
+    ["fail", `${counts.fail} failed`],
+    [
+      "warning",
+      `${counts.warning} warning${counts.warning === 1 ? "" : "s"}`,
+    ],
+    ["info", `${counts.info} info`],
+  ];
+  for (const [state, label] of labels) {
+    if (counts[state] > 0) {
+      const badge = document.createElement("span");
+      badge.classList.add("test-group-badge", `test-group-badge-${state}`);
+      badge.textContent = label;
+      badges.appendChild(badge);
+    }
+  }
+
+  return badges;
 }
 
 function renderTestCase(testCase: Test): HTMLDivElement {
@@ -119,6 +204,10 @@ function renderTestCase(testCase: Test): HTMLDivElement {
   }
   body.appendChild(columns);
 
+  if (testCase.moreFields) {
+    body.appendChild(renderMoreFieldsButton(testCase.moreFields));
+  }
+
   addShowHideListenersToTextCase(root, testCase.defaultVisible);
 
   return root;
@@ -145,7 +234,88 @@ function renderField(field: Field): HTMLDivElement {
   columnDiv.classList.add("test-case-column", "diff-tag-" + field.kind);
   columnDiv.style.gridColumn = `${field.column + 1} / span ${field.span}`;
   columnDiv.style.gridRow = `span ${field.row_span}`;
-  columnDiv.textContent = field.content;
+  if (field.kind === "image") {
+    const img = document.createElement("img");
+    img.src = field.content;
+    img.alt = "";
+    columnDiv.appendChild(img);
+  } else if (field.spans) {
+    for (const span of field.spans) {
+      const spanElement = document.createElement("span");
+      spanElement.classList.toggle("diff-span-changed", span.changed);
+      spanElement.textContent = span.content;
+      columnDiv.appendChild(spanElement);
+    }
+  } else {
+    columnDiv.textContent = field.content;
+  }
 
   return columnDiv;
 }
+
+function moreFieldsLabel(more: MoreFields): string {
+  return `Show more (${more.remaining} remaining)`;
+}
+
+function renderMoreFieldsButton(more: MoreFields): HTMLButtonElement {
+  const button = document.createElement("button");
+  button.type = "button";
+  button.classList.add("test-case-more-fields");
+  button.dataset.url = `/test-case-fields/${more.id}/${more.offset}`;
+  button.textContent = moreFieldsLabel(more);
+  return button;
+}
+
+async function loadMoreFields(button: HTMLButtonElement) {
+  const columns = button
+    .closest(".test-case-content")
+    ?.querySelector<HTMLDivElement>(".test-case-columns");
+  if (!columns) {
+    return;
+  }
+
+  button.disabled = true;
+  let page: FieldPage;
+  try {
+    const response = await fetch(button.dataset.url, {
+      headers: { Accept: "application/json" },
+    });
+    if (!response.ok) {
+      button.textContent =
+        "These results expired, run the code again to see more";
+      return;
+    }
+    page = await response.json();
+  } catch (e) {
+    console.error(e);
+    button.disabled = false;
+    return;
+  }
+
+  for (const field of page.fields) {
+    columns.appendChild(renderField(field));
+  }
+
+  if (page.more) {
+    button.dataset.url = `/test-case-fields/${page.more.id}/${page.more.offset}`;
+    button.textContent = moreFieldsLabel(page.more);
+    button.disabled = false;
+  } else {
+    button.remove();
+  }
+}
+
+/** Handles the buttons of both server rendered test cases and the ones rendered here */
+export function initMoreFieldsButtons() {
+  document.addEventListener("click", (event) => {
+    if (!(event.target instanceof Element)) {
+      return;
+    }
+    const button = event.target.closest<HTMLButtonElement>(
+      "button.test-case-more-fields"
+    );
+    if (button) {
+      loadMoreFields(button);
+    }
+  });
+}
@@ -4,21 +4,37 @@ export type LeaderboardEntry = {
   author_name: string;
   author_id: number;
   points: number;
+  metrics: Record<string, number>;
 };
 
+export type ScoringUnit = "bytes" | "characters" | "lines" | "tokens" | "points";
+
+export type ScoreDirection = "minimize" | "maximize";
+
+export type JudgeFormat = "typescript" | "declarative";
+
 export type Challenge = {
   id: number;
   description: string;
   judge: string;
+  judge_format: JudgeFormat;
   name: string;
   example_code: string;
+  negative_examples: string[];
   category: "code-golf" | "restricted-source";
   status: "public" | "private" | "beta" | "draft";
   author: number;
   is_post_mortem: boolean;
   author_name: string;
   author_avatar: string;
-  unit: string;
+  unit: ScoringUnit;
+  score_direction: ScoreDirection;
+  tiebreaker: string | null;
+  tiebreaker_direction: ScoreDirection;
+  run_timeout: number;
+  judge_timeout: number;
+  max_test_cases: number;
+  max_input_size: number;
 };
 
 export type ScoreInfo = {
@@ -11,11 +11,13 @@ PID=$!
 restart () {
     pkill bwrap
     kill -TERM $PID
+    # Wait for the old runner to drain so the new one can bind the port
+    wait $PID
     $program "${@:1}" &
     PID=$!
 }
 trap restart USR1
-trap 'kill $!; exit' SIGTERM SIGHUP SIGINT
+trap 'kill -TERM $PID; wait $PID; exit' SIGTERM SIGHUP SIGINT
 
 while true; do
     wait
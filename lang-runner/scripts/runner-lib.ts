@@ -6,16 +6,56 @@ export type ResultDisplay =
         output: string;
         input?: string | undefined;
         sep?: string | undefined;
-        displayMode?: "normal" | "filter" | "test" | undefined;
+        displayMode?: DisplayMode | undefined;
         inputSeparator?: string | undefined;
       };
     }
   | { Text: string }
-  | { Run: { input?: string | undefined; output: string; error: string } };
+  | {
+      Run: {
+        input?: string | undefined;
+        output: string;
+        error: string;
+        exit?: RunExit | undefined;
+      };
+    }
+  | { Table: { headers?: string[] | undefined; rows: string[][] } }
+  /**
+   * Byte outputs, one char per byte like binary solutions
+   */
+  | { Hexdump: { output: string; expected?: string | undefined } }
+  /**
+   * A PBM, PGM or PPM image (`P1` to `P6`) of at most 512 by 512 pixels
+   */
+  | { Image: { output: string; expected?: string | undefined } };
+
+/**
+ * How the program of a run ended
+ */
+export type RunExit = {
+  /**
+   * `null` when the program was killed by a signal
+   */
+  code: number | null;
+  signal: string | null;
+  timedOut: boolean;
+  outputTruncated: boolean;
+};
+export type DisplayMode =
+  | "normal"
+  | "filter"
+  | "test"
+  | "grid"
+  | "unordered"
+  | { numeric: { tolerance?: number | undefined } };
 export type Challenge = AsyncGenerator<TestCase, FinalVerdict, undefined>;
 
 export class TestCase {
   name: string | undefined;
+  /**
+   * Test cases with the same group are shown together in a collapsible section
+   */
+  group: string | undefined;
   pass: PassState;
   resultDisplay: ResultDisplay;
 
@@ -34,6 +74,11 @@ export class TestCase {
     return this;
   }
 
+  public setGroup(group: string): this {
+    this.group = group;
+    return this;
+  }
+
   public replaceFailState(state: PassState): this {
     if (this.pass === "Fail") {
       this.pass = state;
@@ -45,17 +90,65 @@ export class TestCase {
 export class FinalVerdict {
   pass: boolean;
   points: number | undefined;
+  /** Secondary scores such as memory use, challenges can use one as a tiebreaker */
+  metrics: Record<string, number>;
+
+  constructor(
+    pass: boolean,
+    points?: number | undefined,
+    metrics?: Record<string, number> | undefined
+  ) {
+    for (const [name, value] of Object.entries(metrics ?? {})) {
+      // JSON has no NaN or Infinity, they would be sent as null
+      if (!Number.isFinite(value)) {
+        throw new Error(
+          `Expected metric "${name}" to be a finite number, got ${value}`
+        );
+      }
+    }
 
-  constructor(pass: boolean, points?: number | undefined) {
     this.pass = pass;
     this.points = points;
+    this.metrics = metrics ?? {};
   }
 }
 
 export type RunCodeResult = {
   stdout: string;
   stderr: string;
+  /**
+   * The signal number plus 128 when the program was killed by a signal
+   */
   exitStatus: number;
+  /**
+   * The name of the signal that killed the program, like `SIGSEGV`
+   */
+  signal: string | null;
+  /**
+   * Whether the run took longer than the timeout in {@link RunOptions}
+   */
+  timedOut: boolean;
+  /**
+   * Whether the program wrote more output than the runner keeps
+   */
+  outputTruncated: boolean;
+};
+
+function runExit(result: RunCodeResult): RunExit {
+  return {
+    code: result.signal === null ? result.exitStatus : null,
+    signal: result.signal,
+    timedOut: result.timedOut,
+    outputTruncated: result.outputTruncated,
+  };
+}
+
+export type RunOptions = {
+  /**
+   * Milliseconds this run can take, not counting compilation. Runs still share the time all
+   * runs can take together, this can only make a single run stricter. Fractions are rounded up.
+   */
+  timeout?: number | undefined;
 };
 
 export interface RunCompiledCodeResult extends RunCodeResult {
@@ -80,7 +173,7 @@ export class StringResult {
   public assertEquals(
     value: string,
     sep: string = "\n",
-    displayMode?: undefined | "test" | "filter" | "normal",
+    displayMode?: undefined | DisplayMode,
     inputSeparator?: string | undefined
   ): TestCase {
     const valid = eqIgnoreTrailingWhitespace(this.text, value);
@@ -98,6 +191,62 @@ export class StringResult {
     return testCase;
   }
 
+  /**
+   * Like {@link assertEquals}, but shows the difference as a grid of characters, which suits
+   * ASCII art
+   */
+  public assertGridEquals(value: string): TestCase {
+    return this.assertEquals(value, "\n", "grid");
+  }
+
+  /**
+   * Passes if the output has the same items as the value in any order, only missing and
+   * extra items are shown
+   */
+  public assertUnorderedEquals(value: string, sep: string = "\n"): TestCase {
+    const testCase = new TestCase(
+      undefined,
+      eqUnordered(this.text, value, sep) ? "Pass" : "Fail",
+      {
+        Diff: {
+          expected: value,
+          output: this.text,
+          sep,
+          input: this.input,
+          displayMode: "unordered",
+        },
+      }
+    );
+    this.context.testCases.push(testCase);
+    return testCase;
+  }
+
+  /**
+   * Passes if the numbers in the output are within the tolerance of the numbers in the value,
+   * see {@link numbersClose}
+   */
+  public assertNumbersClose(
+    value: string,
+    tolerance: number = 1e-6,
+    sep: string = "\n"
+  ): TestCase {
+    const testCase = new TestCase(
+      undefined,
+      eqWithTolerance(this.text, value, tolerance, sep) ? "Pass" : "Fail",
+      {
+        Diff: {
+          expected: value,
+          output: this.text,
+          sep,
+          input: this.input,
+          displayMode: { numeric: { tolerance } },
+        },
+      }
+    );
+    this.context.testCases.push(testCase);
+    return testCase;
+  }
+
   public assert(cb: (k: string) => TestCase): TestCase {
     const testCase = cb(this.text);
     this.context.testCases.push(testCase);
@@ -107,6 +256,8 @@ export class StringResult {
 
 export class RunResult extends StringResult {
   private stderr: string;
+  public timedOut: boolean;
+  public exit: RunExit;
 
   public constructor(
     context: Context,
@@ -115,11 +266,46 @@ export class RunResult extends StringResult {
   ) {
     super(context, result.stdout, input);
     this.stderr = result.stderr;
+    this.timedOut = result.timedOut;
+    this.exit = runExit(result);
   }
 
   public error() {
     return new StringResult(this.context, this.stderr, this.input);
   }
+
+  /**
+   * Fails if the run took longer than the timeout it was given
+   */
+  public assertNoTimeout(): TestCase {
+    const testCase = new TestCase(
+      "Time limit",
+      this.timedOut ? "Fail" : "Pass",
+      {
+        Text: this.timedOut
+          ? "The program took longer than the time limit"
+          : "The program finished within the time limit",
+      }
+    );
+    this.context.testCases.push(testCase);
+    return testCase;
+  }
+
+  /**
+   * Fails unless the program exited by itself with the given code
+   */
+  public assertExitCode(code: number = 0): TestCase {
+    const pass = this.exit.code === code && !this.exit.timedOut;
+    const testCase = new TestCase("Exit code", pass ? "Pass" : "Fail", {
+      Text: pass
+        ? `The program exited with code ${code}`
+        : this.exit.signal !== null
+        ? `Expected the program to exit with code ${code}, but it was killed by ${this.exit.signal}`
+        : `Expected the program to exit with code ${code}, but it exited with code ${this.exit.code}`,
+    });
+    this.context.testCases.push(testCase);
+    return testCase;
+  }
 }
 
 function shuffleAndDeal<T>(
@@ -150,6 +336,10 @@ export type TestCasesOptions<T> = {
   numberOfRuns: number;
   shuffle: boolean;
   compareFunction: (a: string, b: T) => boolean;
+  /**
+   * How the runs are shown, `"test"` puts every test case next to its input
+   */
+  displayMode: DisplayMode;
 };
 
 export type FilterCasesOptions = {
@@ -168,9 +358,14 @@ export class Context {
   public code: string;
   private onRunCallback: (
     code: string,
-    input: string | undefined
+    input: string | undefined,
+    options: RunOptions | undefined
   ) => Promise<RunCompiledCodeResult>;
   public testCases: TestCase[];
+  /**
+   * Runs and test cases without a group of their own are put in this group
+   */
+  public group: string | undefined;
 
   private runs: number = 0;
 
@@ -178,7 +373,8 @@ export class Context {
     code: string,
     onRunCallback: (
       code: string,
-      input: string | undefined
+      input: string | undefined,
+      options: RunOptions | undefined
     ) => Promise<RunCompiledCodeResult>
   ) {
     this.code = code;
@@ -202,6 +398,7 @@ export class Context {
       numberOfRuns: 2,
       shuffle: true,
       compareFunction: (a, b) => eqIgnoreTrailingWhitespace(a, "" + b),
+      displayMode: "test",
       ...overrideOptions,
     };
 
@@ -232,7 +429,8 @@ export class Context {
               expected: cases
                 .map((i) => (i.equal ? i.output : i.expected))
                 .join(options.outputSeparator),
-              displayMode: "test",
+              sep: options.outputSeparator,
+              displayMode: options.displayMode,
               inputSeparator: options.inputSeparator,
             },
           }
@@ -269,30 +467,41 @@ export class Context {
     }
   }
 
-  async run(input?: string | undefined): Promise<RunResult> {
-    return await this.runCode(this.code, input);
+  async run(
+    input?: string | undefined,
+    options?: RunOptions | undefined
+  ): Promise<RunResult> {
+    return await this.runCode(this.code, input, options);
   }
 
-  async runCode(code: string, input?: string | undefined) {
-    const result = await this.onRunCallback(code, input);
+  async runCode(
+    code: string,
+    input?: string | undefined,
+    options?: RunOptions | undefined
+  ) {
+    const result = await this.onRunCallback(code, input, options);
     let { stderr, stdout, compilationResult } = result;
+    let exit = runExit(result);
     const compilationFailed =
       compilationResult && compilationResult.exitStatus !== 0;
     if (compilationFailed) {
       stdout = compilationResult.stdout;
       stderr = compilationResult.stderr;
+      exit = runExit(compilationResult);
     }
     const runDisplay: TestCase = new TestCase(
       `Run #${++this.runs}`,
-      compilationFailed ? "Warning" : "Info",
+      compilationFailed || result.timedOut ? "Warning" : "Info",
       {
         Run: {
           input: input,
           output: stdout,
           error: stderr,
+          exit,
         },
       }
     );
+    runDisplay.group = this.group;
     console.log(JSON.stringify(runDisplay));
     return new RunResult(this, result, input);
   }
@@ -302,11 +511,14 @@ export class Context {
     return testCase;
   }
 
-  noFailures(points?: number | undefined): FinalVerdict {
+  noFailures(
+    points?: number | undefined,
+    metrics?: Record<string, number> | undefined
+  ): FinalVerdict {
     if (this.testCases.every((i) => i.pass !== "Fail")) {
-      return new FinalVerdict(true, points);
+      return new FinalVerdict(true, points, metrics);
     } else {
-      return new FinalVerdict(false, points);
+      return new FinalVerdict(false, points, metrics);
     }
   }
 }
@@ -318,6 +530,79 @@ export const eqIgnoreTrailingWhitespace = (a: string, b: string): boolean => {
   return a_stripped == b_stripped;
 };
 
+const splitItems = (text: string, sep: string): string[] =>
+  text.trimEnd() === ""
+    ? []
+    : text
+        .trimEnd()
+        .split(sep)
+        .map((item) => item.trimEnd());
+
+/**
+ * Whether both texts have the same items in any order, ignoring trailing whitespace
+ */
+export const eqUnordered = (
+  a: string,
+  b: string,
+  sep: string = "\n"
+): boolean => {
+  const [a_items, b_items] = [a, b].map((text) => splitItems(text, sep).sort());
+  return (
+    a_items.length === b_items.length &&
+    a_items.every((item, index) => item === b_items[index])
+  );
+};
+
+/**
+ * Plain decimal numbers with an optional exponent, the same grammar the diff on the website
+ * uses. `Number` alone would also accept forms like `0x10`, `0b1` or `Infinity`.
+ */
+const NUMBER_PATTERN = /^[+-]?(\d+\.?\d*|\.\d+)([eE][+-]?\d+)?$/u;
+
+/**
+ * Compares word by word, words that are both numbers are equal if they differ by at most
+ * the tolerance, relative to `b` if that is larger than 1
+ */
+export const numbersClose = (
+  a: string,
+  b: string,
+  tolerance: number = 1e-6
+): boolean => {
+  const [a_words, b_words] = [a, b].map((text) =>
+    text.split(/\s+/u).filter((word) => word !== "")
+  );
+  return (
+    a_words.length === b_words.length &&
+    a_words.every((a_word, index) => {
+      const b_word = b_words[index];
+      const [a_number, b_number] = [Number(a_word), Number(b_word)];
+      return (
+        a_word === b_word ||
+        (NUMBER_PATTERN.test(a_word) &&
+          NUMBER_PATTERN.test(b_word) &&
+          Math.abs(a_number - b_number) <=
+            tolerance * Math.max(1, Math.abs(b_number)))
+      );
+    })
+  );
+};
+
+/**
+ * Compares line by line with {@link numbersClose}, ignoring trailing whitespace
+ */
+export const eqWithTolerance = (
+  a: string,
+  b: string,
+  tolerance: number = 1e-6,
+  sep: string = "\n"
+): boolean => {
+  const [a_items, b_items] = [a, b].map((text) => splitItems(text, sep));
+  return (
+    a_items.length === b_items.length &&
+    a_items.every((item, index) => numbersClose(item, b_items[index], tolerance))
+  );
+};
+
 export function range(a: number, b?: number): number[] {
   return b === undefined
     ? [...Array(a).keys()]
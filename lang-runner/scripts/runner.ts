@@ -3,6 +3,7 @@ import {
   FinalVerdict,
   RunCodeResult,
   RunCompiledCodeResult,
+  RunOptions,
   TestCase,
 } from "./runner-lib.ts";
 import { readLines } from "./readline.ts";
@@ -22,6 +23,7 @@ type Input = {
   code: string;
   lang: Lang;
   judge: string;
+  binary: boolean;
   max_code_size: number;
   max_input_size: number;
 };
@@ -49,7 +51,8 @@ const { code, judge, max_code_size, max_input_size }: Input = JSON.parse(
 
   const onRunCallback = async (
     program: string,
-    input?: string | undefined
+    input?: string | undefined,
+    options?: RunOptions | undefined
   ): Promise<RunCompiledCodeResult> => {
     if (program.length > max_code_size) {
       throw new Error(
@@ -63,8 +66,21 @@ const { code, judge, max_code_size, max_input_size }: Input = JSON.parse(
       );
     }
 
+    let timeout = options?.timeout;
+    if (timeout !== undefined) {
+      if (!Number.isFinite(timeout) || timeout < 0) {
+        throw new Error(
+          `Expected timeout to be a non-negative number of milliseconds, got ${timeout}`
+        );
+      }
+      timeout = Math.ceil(timeout);
+    }
+
     await Deno.stdout.write(
-      textEncoder.encode(JSON.stringify({ code: program, input }) + "\n")
+      textEncoder.encode(
+        JSON.stringify({ code: program, input, timeout }) +
+          "\n"
+      )
     );
 
     const result = await lines.next();
@@ -80,13 +96,27 @@ const { code, judge, max_code_size, max_input_size }: Input = JSON.parse(
       throw new Error(`Expected input to have at most ${max_input_size} bytes`);
     }
 
+    if (result.value === "InvalidTimeout") {
+      throw new Error(
+        `Expected timeout to be a non-negative number of milliseconds`
+      );
+    }
+
+    if (result.value === "InvalidBinaryCode") {
+      throw new Error(
+        `Binary solutions can only contain characters from \\u0000 to \\u00ff`
+      );
+    }
+
     return result.value as RunCompiledCodeResult;
   };
 
-  const generator = judge_function(new Context(code, onRunCallback));
+  const context = new Context(code, onRunCallback);
+  const generator = judge_function(context);
 
   let value: IteratorResult<TestCase, FinalVerdict>;
   while (!(value = await generator.next()).done) {
+    value.value.group ??= context.group;
     console.log(JSON.stringify(value.value));
   }
   console.log(JSON.stringify(value.value));
@@ -21,6 +21,25 @@ impl<K: Hash + Eq, V> CacheMap<K, V> {
         
         self.inner.entry(key).or_default().clone()
     }
+
+    /// Like `get`, but does not create an entry if the key is missing
+    pub fn get_if_present(&self, key: &K) -> Option<Arc<OnceCell<V>>> {
+        self.inner.get(key).map(|value| value.clone())
+    }
+
+    pub fn remove(&self, key: &K) -> Option<Arc<OnceCell<V>>> {
+        self.inner.remove(key).map(|(_, value)| value)
+    }
+
+    pub fn entries(&self) -> Vec<(K, Arc<OnceCell<V>>)>
+    where
+        K: Clone,
+    {
+        self.inner
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect()
+    }
 }
 
 impl<K: Hash + Eq, V> FromIterator<(K, V)> for CacheMap<K, V> {
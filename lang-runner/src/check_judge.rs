@@ -0,0 +1,147 @@
+use std::{process::Stdio, time::Duration};
+
+use axum::Json;
+use common::JudgeDiagnostic;
+use serde::Deserialize;
+use tokio::process::Command;
+
+use crate::{drain, error::RunLangError};
+
+/// Type checking loads the TypeScript compiler, which is a lot slower than running a judge
+const CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+/// `runner.ts` appends the judge to `runner-lib.ts` with this in between, so the judge is
+/// checked the same way
+const JUDGE_PREFIX: &str = "\nexport default ";
+
+/// Line and column, both 1-based
+type Location = (usize, usize);
+
+#[derive(Deserialize)]
+pub struct CheckJudgeRequest {
+    judge: String,
+}
+
+/// Type checks a judge against `runner-lib.ts` with `deno check`
+pub async fn check_judge_endpoint(
+    Json(CheckJudgeRequest { judge }): Json<CheckJudgeRequest>,
+) -> Result<Json<Vec<JudgeDiagnostic>>, RunLangError> {
+    let Some(_in_flight) = drain::start_request() else {
+        return Err(RunLangError::Draining);
+    };
+
+    let runner_lib = tokio::fs::read_to_string("scripts/runner-lib.ts").await?;
+    let file = tempfile::Builder::new().suffix(".ts").tempfile()?;
+    tokio::fs::write(file.path(), format!("{runner_lib}{JUDGE_PREFIX}{judge}")).await?;
+
+    let output = tokio::time::timeout(
+        CHECK_TIMEOUT,
+        Command::new("/usr/local/bin/deno")
+            .args(["check", "--quiet"])
+            .arg(file.path())
+            .env("NO_COLOR", "TRUE")
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .map_err(|_| RunLangError::JudgeCheckTimedOut)??;
+
+    if output.status.success() {
+        return Ok(Json(vec![]));
+    }
+
+    let judge_start_line = runner_lib.matches('\n').count() + JUDGE_PREFIX.matches('\n').count();
+    let mut diagnostics =
+        parse_diagnostics(&String::from_utf8_lossy(&output.stderr), judge_start_line);
+    if diagnostics.is_empty() {
+        // Deno failed for a reason that isn't a type error, show everything it printed
+        diagnostics.push(JudgeDiagnostic {
+            code: "deno".to_owned(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+            line: None,
+            column: None,
+        });
+    }
+
+    Ok(Json(diagnostics))
+}
+
+/// Parses both the `TS2304 [ERROR]: message` and the `error[TS2304]: message` formats, the
+/// location follows on a later line as `at file:///...:1:2` or `--> file:///...:1:2`.
+/// Syntax errors have the location at the end of the message instead.
+fn parse_diagnostics(stderr: &str, judge_start_line: usize) -> Vec<JudgeDiagnostic> {
+    let mut found: Vec<(&str, &str, Option<Location>)> = vec![];
+
+    for line in stderr.lines().map(str::trim) {
+        let header = if let Some(rest) = line.strip_prefix("error[")
+            && let Some((code, message)) = rest.split_once("]: ")
+        {
+            Some((code, message))
+        } else if line.starts_with("TS")
+            && let Some((code, message)) = line.split_once(" [ERROR]: ")
+        {
+            Some((code, message))
+        } else if let Some(message) = line.strip_prefix("error: ")
+            && !message.starts_with("Type checking failed")
+        {
+            Some(("SyntaxError", message))
+        } else {
+            None
+        };
+
+        if let Some((code, message)) = header {
+            found.push(match message.rsplit_once(" at file://") {
+                Some((message, location)) => (code, message, parse_location(location)),
+                None => (code, message, None),
+            });
+        } else if let Some((_, _, location @ None)) = found.last_mut()
+            && let Some(at) = line
+                .strip_prefix("at file://")
+                .or_else(|| line.strip_prefix("--> file://"))
+        {
+            *location = parse_location(at);
+        }
+    }
+
+    found
+        .into_iter()
+        .map(|(code, message, location)| {
+            to_judge_diagnostic(code, message, location, judge_start_line)
+        })
+        .collect()
+}
+
+/// Parses the `line:column` at the end of `/path/to/file.ts:1:2`
+fn parse_location(location: &str) -> Option<Location> {
+    let mut parts = location.rsplitn(3, ':');
+    let column = parts.next()?.parse().ok()?;
+    let line = parts.next()?.parse().ok()?;
+    Some((line, column))
+}
+
+/// Moves the location from the combined file into the judge
+fn to_judge_diagnostic(
+    code: &str,
+    message: &str,
+    location: Option<Location>,
+    judge_start_line: usize,
+) -> JudgeDiagnostic {
+    let location = location.and_then(|(line, column)| {
+        let line = line.checked_sub(judge_start_line)?;
+        let column = match line {
+            // The first line of the judge comes after `export default `
+            1 => column
+                .saturating_sub(JUDGE_PREFIX.trim_start().len())
+                .max(1),
+            _ => column,
+        };
+        (line >= 1).then_some((line, column))
+    });
+
+    JudgeDiagnostic {
+        code: code.to_owned(),
+        message: message.to_owned(),
+        line: location.map(|(line, _)| line),
+        column: location.map(|(_, column)| column),
+    }
+}
@@ -0,0 +1,93 @@
+use std::{
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use axum::http::StatusCode;
+use tokio::sync::Notify;
+
+/// How long running judges get to finish after a shutdown signal before they are cut off.
+/// Keep this below the `stop_grace_period` in the docker compose file.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+static DRAINING: AtomicBool = AtomicBool::new(false);
+static CUT_OFF: AtomicBool = AtomicBool::new(false);
+static CUT_OFF_NOTIFY: Notify = Notify::const_new();
+
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+static IDLE_NOTIFY: Notify = Notify::const_new();
+
+pub fn is_draining() -> bool {
+    DRAINING.load(Ordering::SeqCst)
+}
+
+/// Marks a request as in flight until the guard is dropped. Returns `None` if the runner
+/// is draining and should not accept new work.
+pub fn start_request() -> Option<InFlightGuard> {
+    IN_FLIGHT.fetch_add(1, Ordering::SeqCst);
+    let guard = InFlightGuard;
+    // Checked after incrementing so `drain` can never miss a request that slipped in
+    (!is_draining()).then_some(guard)
+}
+
+pub struct InFlightGuard;
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if IN_FLIGHT.fetch_sub(1, Ordering::SeqCst) == 1 {
+            IDLE_NOTIFY.notify_waiters();
+        }
+    }
+}
+
+/// Resolves once the drain deadline has passed, anything still running at that point
+/// should give up and tell the main server to retry
+pub async fn cut_off() {
+    loop {
+        let notified = CUT_OFF_NOTIFY.notified();
+        if CUT_OFF.load(Ordering::SeqCst) {
+            return;
+        }
+        notified.await;
+    }
+}
+
+/// Stops accepting new work and waits for the running judges to finish, or for the
+/// deadline to pass, whichever happens first.
+pub async fn drain() {
+    DRAINING.store(true, Ordering::SeqCst);
+    eprintln!(
+        "Draining, waiting for {} request(s) to finish",
+        IN_FLIGHT.load(Ordering::SeqCst)
+    );
+
+    let wait_for_idle = async {
+        loop {
+            let notified = IDLE_NOTIFY.notified();
+            if IN_FLIGHT.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            notified.await;
+        }
+    };
+
+    if tokio::time::timeout(DRAIN_TIMEOUT, wait_for_idle)
+        .await
+        .is_err()
+    {
+        eprintln!(
+            "Drain deadline passed, cutting off {} request(s)",
+            IN_FLIGHT.load(Ordering::SeqCst)
+        );
+        CUT_OFF.store(true, Ordering::SeqCst);
+        CUT_OFF_NOTIFY.notify_waiters();
+    }
+}
+
+pub async fn readiness_endpoint() -> (StatusCode, &'static str) {
+    if is_draining() {
+        (StatusCode::SERVICE_UNAVAILABLE, "Draining")
+    } else {
+        (StatusCode::OK, "Ready")
+    }
+}
@@ -22,6 +22,10 @@ pub enum RunProcessError {
     InvalidUtf8,
     CodeTooLarge,
     InputTooLarge,
+    /// A binary solution was modified by the judge to contain a char that is not a byte
+    InvalidBinaryCode,
+    /// The judge asked for a timeout that is negative or not a number
+    InvalidTimeout,
 }
 
 impl From<std::io::Error> for RunProcessError {
@@ -45,6 +49,12 @@ pub enum RunLangError {
         tokio::sync::AcquireError,
     ),
     MaxTestCasesExceeded,
+    /// The run limits of the request are outside [`common::RunLimits::MAX`]
+    InvalidLimits(#[allow(unused)] &'static str),
+    /// `deno check` took too long to type check a judge
+    JudgeCheckTimedOut,
+    /// The runner is shutting down, the main server should retry the request
+    Draining,
 }
 
 impl From<std::io::Error> for RunLangError {
@@ -55,9 +65,10 @@ impl From<std::io::Error> for RunLangError {
 
 impl IntoResponse for RunLangError {
     fn into_response(self) -> axum::response::Response {
-        Response::builder()
-            .status(503)
-            .body(Body::from(format!("{self:?}")))
-            .unwrap()
+        let mut response = Response::builder().status(503);
+        if matches!(self, RunLangError::Draining) {
+            response = response.header("Retry-After", "1");
+        }
+        response.body(Body::from(format!("{self:?}"))).unwrap()
     }
 }
@@ -1,16 +1,25 @@
 use common::langs::{LANGS, Lang};
-use std::{path::PathBuf, process::Stdio, sync::Arc};
+use std::{path::PathBuf, process::Stdio, sync::Arc, time::Instant};
 use tokio::process::Command;
+use tracing::Instrument;
 
-use crate::{cachemap::CacheMap, error::RunProcessError};
+use crate::{cachemap::CacheMap, error::RunProcessError, metrics::record_install};
 
+#[tracing::instrument(skip_all, fields(lang = lang.plugin_name))]
 pub async fn install_plugin(lang: &'static Lang) -> Result<CacheMap<String, ()>, RunProcessError> {
-    println!("Installing language version {}", lang.display_name);
+    tracing::info!("Installing language plugin {}", lang.display_name);
+    let start_time = Instant::now();
     let plugin_install_output = Command::new("asdf")
         .args(["plugin", "add", lang.plugin_name, lang.plugin])
         .stderr(Stdio::inherit())
         .status()
         .await?;
+    record_install(
+        lang.plugin_name,
+        "plugin",
+        plugin_install_output.success(),
+        start_time.elapsed(),
+    );
     if !plugin_install_output.success() {
         return Err(RunProcessError::NonZeroStatusCode(
             plugin_install_output.code(),
@@ -19,13 +28,15 @@ pub async fn install_plugin(lang: &'static Lang) -> Result<CacheMap<String, ()>,
     Ok(CacheMap::new())
 }
 
+#[tracing::instrument(skip(lang), fields(lang = lang.plugin_name))]
 pub async fn install_language_version(
     lang: &'static Lang,
     version: &str,
 ) -> Result<(), RunProcessError> {
-    println!(
+    tracing::info!(
         "Installing language version {} {}",
-        lang.display_name, version
+        lang.display_name,
+        version
     );
     let mut command = Command::new("asdf");
     command
@@ -36,7 +47,14 @@ pub async fn install_language_version(
         command.env(env.0, env.1);
     }
 
+    let start_time = Instant::now();
     let status = command.status().await?;
+    record_install(
+        lang.plugin_name,
+        "version",
+        status.success(),
+        start_time.elapsed(),
+    );
 
     if !status.success() {
         return Err(RunProcessError::NonZeroStatusCode(status.code()));
@@ -44,13 +62,14 @@ pub async fn install_language_version(
     Ok(())
 }
 
+#[tracing::instrument(skip(versions))]
 pub async fn install_lang(
     lang_name: String,
     version: &str,
     versions: Arc<CacheMap<String, CacheMap<String, ()>>>,
 ) -> Result<(), RunProcessError> {
     let version = version.to_owned();
-    tokio::spawn(async move {
+    let task = async move {
         let lang = match LANGS.get(&lang_name) {
             Some(e) => e,
             None => panic!("Unexpected lang {lang_name}"),
@@ -68,14 +87,23 @@ pub async fn install_lang(
             .await?;
 
         Ok(())
-    })
-    .await
-    .map_err(|_| RunProcessError::JoinFail)?
+    };
+    tokio::spawn(task.in_current_span())
+        .await
+        .map_err(|_| RunProcessError::JoinFail)?
 }
 
 pub async fn get_lang_directory(lang: &Lang, version: &str) -> Result<PathBuf, RunProcessError> {
+    get_version_directory(lang.plugin_name, version).await
+}
+
+#[tracing::instrument]
+pub async fn get_version_directory(
+    plugin_name: &str,
+    version: &str,
+) -> Result<PathBuf, RunProcessError> {
     let lang_folder = Command::new("asdf")
-        .args(["where", lang.plugin_name, version])
+        .args(["where", plugin_name, version])
         .stderr(Stdio::inherit())
         .output()
         .await?;
@@ -3,7 +3,7 @@ use std::{
     time::{Duration, Instant},
 };
 
-use common::{JudgeResult, RunLangOutput, TestCase, TimerType, Timers, langs::LANGS};
+use common::{JudgeResult, Metrics, RunLangOutput, RunLimits, TestCase, TimerType, langs::LANGS};
 use serde::{Deserialize, Serialize};
 use tokio::{
     io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
@@ -11,16 +11,20 @@ use tokio::{
     sync::mpsc::Sender,
 };
 
-use crate::{error::RunLangError, run::RunLangContext, stopwatch::start_stopwatch};
+use crate::{
+    error::{RunLangError, RunProcessError},
+    run::RunLangContext,
+    stopwatch::start_stopwatch,
+};
 
-const MAX_TEST_CASES: usize = 50;
-const TIMEOUT: u64 = 3;
 const MAX_CODE_SIZE: usize = 64 * 1024;
 
 #[derive(Deserialize, Debug)]
 struct FinalVerdict {
     pass: bool,
     points: Option<i32>,
+    #[serde(default)]
+    metrics: Metrics,
 }
 
 #[derive(Serialize)]
@@ -28,6 +32,7 @@ struct RunnerInput<'a> {
     lang: &'a str,
     code: &'a str,
     judge: &'a str,
+    binary: bool,
     max_code_size: usize,
     max_input_size: usize,
 }
@@ -36,6 +41,8 @@ struct RunnerInput<'a> {
 struct RunRequest {
     code: String,
     input: Option<String>,
+    /// In milliseconds, on top of the time all runs can take together. Can have a fraction.
+    timeout: Option<f64>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -46,50 +53,58 @@ enum JudgeResponse {
     FinalVerdict(FinalVerdict),
 }
 
+/// Tells the judge that a run request was rejected, `runner.ts` turns this into an exception
+async fn reject_run_request(
+    stdin: &mut ChildStdin,
+    error: RunProcessError,
+) -> Result<(), RunLangError> {
+    stdin
+        .write_all(
+            &serde_json::to_vec(&error)
+                .map_err(|e| RunLangError::RunLang(RunProcessError::SerializationFailed(e)))?,
+        )
+        .await?;
+    Ok(())
+}
+
 async fn handle_judge_command(
     data: JudgeResponse,
     judge_result_ref: &mut JudgeResult,
     sender: &mut Sender<TimerType>,
     stdin: &mut ChildStdin,
     context: &mut RunLangContext,
+    limits: &RunLimits,
 ) -> Result<(), RunLangError> {
     match data {
         JudgeResponse::RunRequest(run_request) => {
-            if run_request.code.len() > MAX_CODE_SIZE {
-                stdin
-                    .write_all(
-                        &serde_json::to_vec(&crate::error::RunProcessError::CodeTooLarge).map_err(
-                            |e| {
-                                RunLangError::RunLang(
-                                    crate::error::RunProcessError::SerializationFailed(e),
-                                )
-                            },
-                        )?,
-                    )
-                    .await?;
-                return Ok(());
+            let Some(code) = context.code_bytes(&run_request.code) else {
+                return reject_run_request(stdin, RunProcessError::InvalidBinaryCode).await;
+            };
+
+            if code.len() > MAX_CODE_SIZE {
+                return reject_run_request(stdin, RunProcessError::CodeTooLarge).await;
             }
 
             if run_request
                 .input
                 .as_deref()
-                .is_some_and(|i| i.len() > MAX_CODE_SIZE)
+                .is_some_and(|i| i.len() > limits.max_input_size as usize)
             {
-                stdin
-                    .write_all(
-                        &serde_json::to_vec(&crate::error::RunProcessError::InputTooLarge)
-                            .map_err(|e| {
-                                RunLangError::RunLang(
-                                    crate::error::RunProcessError::SerializationFailed(e),
-                                )
-                            })?,
-                    )
-                    .await?;
-                return Ok(());
+                return reject_run_request(stdin, RunProcessError::InputTooLarge).await;
             }
 
+            let timeout = match run_request.timeout {
+                Some(timeout) => match Duration::try_from_secs_f64(timeout / 1000.0) {
+                    Ok(timeout) => Some(timeout),
+                    Err(_) => {
+                        return reject_run_request(stdin, RunProcessError::InvalidTimeout).await;
+                    }
+                },
+                None => None,
+            };
+
             let result = context
-                .run(&run_request.code, run_request.input.as_deref(), sender)
+                .run(&code, run_request.input.as_deref(), timeout, sender)
                 .await
                 .map_err(RunLangError::RunLang)?;
 
@@ -102,25 +117,31 @@ async fn handle_judge_command(
         JudgeResponse::TestCase(test_case) => {
             judge_result_ref.test_cases.push(test_case);
 
-            if judge_result_ref.test_cases.len() > MAX_TEST_CASES {
+            if judge_result_ref.test_cases.len() > limits.max_test_cases as usize {
                 Err(RunLangError::MaxTestCasesExceeded)?;
             }
         }
         JudgeResponse::FinalVerdict(final_verdict) => {
-            println!("final_verdict: {final_verdict:?}");
+            tracing::debug!(?final_verdict, "Received final verdict");
             judge_result_ref.pass = final_verdict.pass;
             judge_result_ref.points = final_verdict.points;
+            judge_result_ref.metrics = final_verdict.metrics;
         }
     }
 
     Ok(())
 }
 
+/// Binary solutions are passed as a string with one char per byte, see
+/// `common::binary::to_binary_string`
+#[tracing::instrument(skip(code, judge))]
 pub async fn run_lang_with_judge(
     language: &str,
     version: &str,
     code: &str,
     judge: &str,
+    binary: bool,
+    limits: RunLimits,
 ) -> Result<RunLangOutput, RunLangError> {
     let lang = LANGS.get(language).ok_or(RunLangError::RunLang(
         crate::error::RunProcessError::NoSuchLanguage,
@@ -130,8 +151,9 @@ pub async fn run_lang_with_judge(
         lang: language,
         code,
         judge,
+        binary,
         max_code_size: MAX_CODE_SIZE,
-        max_input_size: MAX_CODE_SIZE,
+        max_input_size: limits.max_input_size as usize,
     })
     .map_err(|e| RunLangError::RunLang(crate::error::RunProcessError::SerializationFailed(e)))?;
 
@@ -146,6 +168,7 @@ pub async fn run_lang_with_judge(
         .stdout(Stdio::piped())
         .stdin(Stdio::piped())
         .stderr(Stdio::piped())
+        .kill_on_drop(true)
         .spawn()
         .map_err(RunLangError::IOError)?;
 
@@ -153,7 +176,7 @@ pub async fn run_lang_with_judge(
     let mut lines =
         BufReader::new(command.stdout.take().expect("Command should have stdout")).lines();
 
-    let mut context = RunLangContext::new(language, version)
+    let mut context = RunLangContext::new(language, version, binary)
         .await
         .map_err(RunLangError::RunLang)?;
 
@@ -165,14 +188,11 @@ pub async fn run_lang_with_judge(
         pass: false,
         test_cases: vec![],
         points: None,
+        metrics: Metrics::new(),
     };
     let judge_result_ref = &mut judge_result;
     let (out, timers) = start_stopwatch(
-        Timers {
-            judge: Duration::from_secs(1) + lang.extra_runtime.judge,
-            run: Duration::from_secs(TIMEOUT) + lang.extra_runtime.run,
-            compile: Duration::from_secs(1) + lang.extra_runtime.compile,
-        },
+        limits.timers(&lang.extra_runtime),
         receiver,
         Box::pin(async move {
             while let Some(line) = lines.next_line().await? {
@@ -185,6 +205,7 @@ pub async fn run_lang_with_judge(
                     &mut sender,
                     &mut stdin,
                     &mut context,
+                    &limits,
                 )
                 .await?;
             }
@@ -210,6 +231,13 @@ pub async fn run_lang_with_judge(
     let mut error = String::new();
     stderr.read_to_string(&mut error).await?;
 
+    tracing::info!(
+        pass = judge_result.pass,
+        timed_out,
+        test_cases = judge_result.test_cases.len(),
+        "Judge finished"
+    );
+
     Ok(RunLangOutput {
         tests: judge_result,
         stderr: error,
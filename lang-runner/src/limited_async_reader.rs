@@ -16,12 +16,15 @@ impl Unpin for LimitedAsyncPipeReader {}
 
 pub struct LimitedAsyncPipeReaderOutput {
     value: Vec<u8>,
-    // Reserved for future use
-    #[allow(unused)]
     truncated: bool,
 }
 
 impl LimitedAsyncPipeReaderOutput {
+    /// Whether the process wrote more than the maximum length, the rest was dropped
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
     pub fn into_string(self) -> Result<String, RunProcessError> {
         String::from_utf8(self.value).map_err(|_| RunProcessError::InvalidUtf8)
     }
@@ -1,23 +1,37 @@
 mod async_process_with_extra_pipes;
 mod cachemap;
+mod check_judge;
+mod drain;
 mod error;
 mod install_lang;
 mod judge;
 mod limited_async_reader;
 mod limited_async_writer;
+mod metrics;
 mod process_message;
 mod run;
+mod self_test;
 mod stopwatch;
+mod uninstall_lang;
 
 use std::sync::Arc;
 
-use axum::{Json, Router, extract::State, routing::get};
+use axum::{
+    Json, Router,
+    extract::State,
+    http::HeaderMap,
+    routing::{get, post},
+};
 use cachemap::CacheMap;
-use common::RunLangOutput;
+use common::{
+    RunLangOutput, RunLimits,
+    telemetry::{REQUEST_ID_HEADER, init_tracing, set_parent_from_headers},
+};
 use error::RunLangError;
 use process_message::{get_lang_versions, process_message};
 use serde::{Deserialize, Serialize};
 use tokio::signal;
+use tracing::Instrument;
 
 #[derive(Serialize, Debug, Deserialize)]
 pub struct Message {
@@ -25,13 +39,26 @@ pub struct Message {
     version: String,
     code: String,
     judge: String,
+    /// Set for solutions submitted as raw bytes, `code` then holds one char per byte
+    #[serde(default)]
+    binary: bool,
+    #[serde(default)]
+    limits: RunLimits,
 }
 
 #[tokio::main]
 async fn main() {
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() == Some("self-test") {
+        let _telemetry = init_tracing("lang-runner");
+        let lang_versions = Arc::new(get_lang_versions().await);
+        std::process::exit(self_test::self_test_cli(args.collect(), lang_versions).await);
+    }
+
     println!("Starting server");
     // initialize tracing
-    tracing_subscriber::fmt::init();
+    let _telemetry = init_tracing("lang-runner");
+    metrics::install_metrics_recorder();
 
     let lang_versions = get_lang_versions().await;
 
@@ -40,6 +67,15 @@ async fn main() {
         // `GET /` goes to `root`
         .route("/", get(root).post(handle_message))
         .route("/lang-versions", get(lang_versions_endpoint))
+        .route("/check-judge", post(check_judge::check_judge_endpoint))
+        .route("/ready", get(drain::readiness_endpoint))
+        .route("/self-test", post(self_test::self_test_endpoint))
+        .route(
+            "/installed-versions",
+            get(uninstall_lang::installed_versions_endpoint),
+        )
+        .route("/uninstall", post(uninstall_lang::uninstall_endpoint))
+        .route("/metrics", get(metrics::metrics_endpoint))
         .with_state(Arc::new(lang_versions));
 
     // run our app with hyper, listening globally on port 3000
@@ -65,10 +101,29 @@ async fn lang_versions_endpoint(
 #[axum::debug_handler]
 async fn handle_message(
     lang_versions: State<Arc<CacheMap<String, CacheMap<String, ()>>>>,
+    headers: HeaderMap,
     message: Json<Message>,
 ) -> Result<Json<RunLangOutput>, RunLangError> {
-    let result = process_message(message.0, lang_versions.0).await?;
-    Ok(Json(result))
+    let Some(_in_flight) = drain::start_request() else {
+        return Err(RunLangError::Draining);
+    };
+
+    let request_id = headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let span = tracing::info_span!(
+        "handle_message",
+        request_id,
+        lang = %message.lang,
+        version = %message.version,
+    );
+    set_parent_from_headers(&span, &headers);
+
+    tokio::select! {
+        result = process_message(message.0, lang_versions.0).instrument(span) => Ok(Json(result?)),
+        _ = drain::cut_off() => Err(RunLangError::Draining),
+    }
 }
 
 async fn shutdown_signal() {
@@ -93,4 +148,6 @@ async fn shutdown_signal() {
         _ = ctrl_c => {},
         _ = terminate => {},
     }
+
+    drain::drain().await;
 }
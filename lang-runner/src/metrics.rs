@@ -0,0 +1,115 @@
+use std::{sync::OnceLock, time::Duration};
+
+use ::metrics::{counter, gauge, histogram};
+use common::{RunLangOutput, Timers};
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+
+use crate::error::RunLangError;
+
+/// Buckets in seconds, the largest runs are bounded by the judge timeout plus the
+/// extra runtime of slow languages like Kotlin
+const DURATION_BUCKETS: &[f64] = &[
+    0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0, 128.0, 256.0,
+];
+
+static PROMETHEUS_HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+pub fn install_metrics_recorder() {
+    let handle = PrometheusBuilder::new()
+        .set_buckets(DURATION_BUCKETS)
+        .expect("Expected the list of buckets to not be empty")
+        .install_recorder()
+        .expect("Failed to install the metrics recorder");
+
+    PROMETHEUS_HANDLE
+        .set(handle)
+        .expect("The metrics recorder should only be installed once");
+}
+
+pub async fn metrics_endpoint() -> String {
+    PROMETHEUS_HANDLE
+        .get()
+        .map(PrometheusHandle::render)
+        .unwrap_or_default()
+}
+
+pub fn record_run(language: &str, result: &Result<RunLangOutput, RunLangError>, elapsed: Duration) {
+    let outcome = match result {
+        Ok(output) if output.timed_out => "timeout",
+        Ok(output) if output.tests.pass => "pass",
+        Ok(_) => "fail",
+        Err(_) => "error",
+    };
+
+    counter!("runner_runs_total", "language" => language.to_owned(), "outcome" => outcome)
+        .increment(1);
+    histogram!("runner_run_duration_seconds", "language" => language.to_owned()).record(elapsed);
+
+    if let Ok(output) = result {
+        record_timers(language, &output.timers);
+
+        if output.timed_out {
+            counter!("runner_timeouts_total", "language" => language.to_owned()).increment(1);
+        }
+    }
+}
+
+fn record_timers(language: &str, timers: &Timers) {
+    for (timer, duration) in [
+        ("compile", timers.compile),
+        ("run", timers.run),
+        ("judge", timers.judge),
+    ] {
+        histogram!(
+            "runner_timer_seconds",
+            "language" => language.to_owned(),
+            "timer" => timer
+        )
+        .record(duration);
+    }
+}
+
+/// Tracks how many requests are waiting on the run semaphore. The returned guard
+/// should be dropped once the permit has been acquired.
+pub fn track_queued_run() -> QueuedRunGuard {
+    gauge!("runner_queue_depth").increment(1);
+    QueuedRunGuard
+}
+
+pub struct QueuedRunGuard;
+
+impl Drop for QueuedRunGuard {
+    fn drop(&mut self) {
+        gauge!("runner_queue_depth").decrement(1);
+    }
+}
+
+/// Tracks how many judges are currently running. Drop the guard when the run is done.
+pub fn track_active_run() -> ActiveRunGuard {
+    gauge!("runner_active_runs").increment(1);
+    ActiveRunGuard
+}
+
+pub struct ActiveRunGuard;
+
+impl Drop for ActiveRunGuard {
+    fn drop(&mut self) {
+        gauge!("runner_active_runs").decrement(1);
+    }
+}
+
+pub fn record_install(language: &str, kind: &'static str, success: bool, elapsed: Duration) {
+    counter!(
+        "runner_installs_total",
+        "language" => language.to_owned(),
+        "kind" => kind,
+        "result" => if success { "success" } else { "failure" }
+    )
+    .increment(1);
+    histogram!(
+        "runner_install_duration_seconds",
+        "language" => language.to_owned(),
+        "kind" => kind
+    )
+    .record(elapsed);
+}
@@ -1,37 +1,77 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
 use common::RunLangOutput;
-use tokio::process::Command;
+use tokio::{
+    process::Command,
+    sync::{AcquireError, SemaphorePermit},
+};
+use tracing::Instrument;
 
 use crate::{
     Message, cachemap::CacheMap, error::RunLangError, install_lang::install_lang,
-    judge::run_lang_with_judge,
+    judge::run_lang_with_judge, metrics,
 };
 
 const MAX_CONCURRENT_RUNS: usize = 4;
 static RUNS_SEMAPHORE: tokio::sync::Semaphore =
     tokio::sync::Semaphore::const_new(MAX_CONCURRENT_RUNS);
 
+/// Waits for all running judges to finish and blocks new ones from starting until the
+/// returned permit is dropped
+pub async fn pause_runs() -> Result<SemaphorePermit<'static>, AcquireError> {
+    RUNS_SEMAPHORE
+        .acquire_many(MAX_CONCURRENT_RUNS as u32)
+        .await
+}
+
 pub async fn process_message(
     message: Message,
     lang_versions: Arc<CacheMap<String, CacheMap<String, ()>>>,
 ) -> Result<RunLangOutput, RunLangError> {
-    install_lang(message.lang.clone(), &message.version, lang_versions)
-        .await
-        .map_err(RunLangError::PluginInstallFailure)?;
+    // Challenges are validated when they are saved, this also covers old rows and requests
+    // that don't come from the main server
+    message
+        .limits
+        .validate()
+        .map_err(RunLangError::InvalidLimits)?;
+
+    // Installing first so a slow install doesn't hold a run slot
+    install_lang(
+        message.lang.clone(),
+        &message.version,
+        lang_versions.clone(),
+    )
+    .await
+    .map_err(RunLangError::PluginInstallFailure)?;
 
+    let queued = metrics::track_queued_run();
     let _semaphore = RUNS_SEMAPHORE
         .acquire()
+        .instrument(tracing::info_span!("wait_for_run_slot"))
         .await
         .map_err(RunLangError::SemaphoreError)?;
+    drop(queued);
+
+    // The version could have been uninstalled while waiting for the slot, uninstalling pauses
+    // runs so it can't happen after this. Only installs again in that case.
+    install_lang(message.lang.clone(), &message.version, lang_versions)
+        .await
+        .map_err(RunLangError::PluginInstallFailure)?;
+
+    let _active = metrics::track_active_run();
+    let start_time = Instant::now();
     let output = run_lang_with_judge(
         &message.lang,
         &message.version,
         &message.code,
         &message.judge,
+        message.binary,
+        message.limits,
     )
-    .await?;
-    Ok(output)
+    .await;
+    metrics::record_run(&message.lang, &output, start_time.elapsed());
+
+    output
 }
 
 async fn get_versions_for_language(line: &str) -> (String, CacheMap<String, ()>) {
@@ -47,7 +87,7 @@ async fn get_versions_for_language(line: &str) -> (String, CacheMap<String, ()>)
         .unwrap();
 
     if !versions.status.success() {
-        println!("Finding versions failed");
+        tracing::warn!("Finding versions failed for {name}");
     }
 
     (
@@ -2,13 +2,16 @@ use std::borrow::Cow;
 use std::collections::HashMap;
 use std::collections::hash_map::Entry;
 use std::ffi::{CStr, CString};
-use std::fmt::Write;
+use std::time::Duration;
 
 use common::TimerType;
+use common::binary::from_binary_string;
 use common::langs::{LANGS, Lang};
 use nix::libc::{STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO};
+use nix::sys::signal::Signal;
 use serde::Serialize;
 use tempfile::TempDir;
+use tracing::Instrument;
 
 use crate::async_process_with_extra_pipes::{AsyncProcessWithCustomPipes, SignalOrStatus};
 use crate::error::RunProcessError;
@@ -20,19 +23,52 @@ pub struct RunCodeResult {
     pub stdout: String,
     pub stderr: String,
     pub exit_status: i32,
+    /// The name of the signal that killed the process, if any
+    pub signal: Option<&'static str>,
+    /// Set when the run took longer than the timeout the judge asked for
+    pub timed_out: bool,
+    /// Set when stdout or stderr was longer than the runner keeps
+    pub output_truncated: bool,
+}
+
+/// Stops a run that takes longer than the judge asked for, dropping the sandbox kills it
+async fn run_with_timeout(
+    run: impl Future<Output = Result<RunCodeResult, RunProcessError>>,
+    timeout: Option<Duration>,
+) -> Result<RunCodeResult, RunProcessError> {
+    let Some(timeout) = timeout else {
+        return run.await;
+    };
+
+    match tokio::time::timeout(timeout, run).await {
+        Ok(result) => result,
+        Err(_) => Ok(RunCodeResult {
+            stdout: String::new(),
+            stderr: format!("Run timed out after {} ms", timeout.as_millis()),
+            exit_status: Signal::SIGTERM as i32 + 128,
+            signal: Some(Signal::SIGTERM.as_str()),
+            timed_out: true,
+            output_truncated: false,
+        }),
+    }
 }
 
 pub struct RunLangContext {
     tmp_folder: TempDir,
-    compiled_programs: HashMap<String, CString>,
+    compiled_programs: HashMap<Vec<u8>, CString>,
     lang: &'static Lang,
+    binary: bool,
     lang_folder: CString,
     run_command: Vec<CString>,
     compile_command: Option<Vec<CString>>,
 }
 
 impl RunLangContext {
-    pub async fn new(lang_name: &str, lang_version: &str) -> Result<Self, RunProcessError> {
+    pub async fn new(
+        lang_name: &str,
+        lang_version: &str,
+        binary: bool,
+    ) -> Result<Self, RunProcessError> {
         let lang = LANGS
             .get(lang_name)
             .ok_or(RunProcessError::NoSuchLanguage)?;
@@ -48,6 +84,7 @@ impl RunLangContext {
             tmp_folder: TempDir::new()?,
             compiled_programs: HashMap::new(),
             lang,
+            binary,
             lang_folder,
             run_command: Self::run_substitutions(lang.run_command, lang.extension),
             compile_command: lang
@@ -71,10 +108,23 @@ impl RunLangContext {
             .collect()
     }
 
+    /// The exact bytes to mount as the source file. Returns `None` if a binary solution
+    /// contains a char that does not fit in a byte.
+    pub fn code_bytes<'a>(&self, code: &'a str) -> Option<Cow<'a, [u8]>> {
+        if self.binary {
+            from_binary_string(code).map(Cow::Owned)
+        } else {
+            Some(Cow::Borrowed(code.as_bytes()))
+        }
+    }
+
+    /// The timeout only covers running the program, not compiling it
+    #[tracing::instrument(name = "run_request", skip_all, fields(lang = self.lang.plugin_name))]
     pub async fn run(
         &mut self,
-        code: &str,
+        code: &[u8],
         input: Option<&str>,
+        timeout: Option<Duration>,
         sender: &mut tokio::sync::mpsc::Sender<TimerType>,
     ) -> Result<RunCodeResult, RunProcessError> {
         let code_mount = match self.lang.extension {
@@ -88,18 +138,18 @@ impl RunLangContext {
         match &self.compile_command {
             None => {
                 let _ = sender.send(TimerType::Run).await;
-                eprintln!("Starting run with lang {}", self.lang.display_name);
                 let mut sandbox = RunInSandboxBuilder::new(
                     self.lang,
                     &self.lang_folder,
                     self.run_command.as_slice(),
                 )
-                .mount_string(&code_mount, code.as_bytes());
+                .mount_string(&code_mount, code);
                 if let Some(input) = input {
                     sandbox = sandbox.set_input(input.as_bytes());
                 }
-                let result = sandbox.run().await;
-                eprintln!("Finished run with lang {}", self.lang.display_name);
+                let result = run_with_timeout(sandbox.run(), timeout)
+                    .instrument(tracing::info_span!("execute"))
+                    .await;
 
                 let _ = sender.send(TimerType::Judge).await;
                 result
@@ -128,8 +178,9 @@ impl RunLangContext {
                         let result =
                             RunInSandboxBuilder::new(self.lang, &self.lang_folder, compile_command)
                                 .mount_folder(&folder_cstr, c"/artifact")
-                                .mount_string(&code_mount, code.as_bytes())
+                                .mount_string(&code_mount, code)
                                 .run()
+                                .instrument(tracing::info_span!("compile"))
                                 .await?;
 
                         (t.insert(folder_cstr) as &CString, result.stderr)
@@ -145,7 +196,9 @@ impl RunLangContext {
                     sandbox = sandbox.set_input(input.as_bytes());
                 }
 
-                let mut result = sandbox.run().await?;
+                let mut result = run_with_timeout(sandbox.run(), timeout)
+                    .instrument(tracing::info_span!("execute"))
+                    .await?;
                 let _ = sender.send(TimerType::Judge).await;
                 result.stderr.insert_str(0, &stderr);
                 Ok(result)
@@ -269,43 +322,40 @@ impl<'a> RunInSandboxBuilder<'a> {
             .await
             .map_err(RunProcessError::IOError)?;
 
-        println!("Starting to wait on child stderr");
-        let mut child_stderr = output
+        tracing::trace!("Starting to wait on child stderr");
+        let child_stderr = output
             .outputs
             .remove(&STDERR_FILENO)
             .expect("Expected a stderr pipe to exist")
             .await
-            .expect("Task panicked")?
-            .into_string()?;
-        println!("Finished waiting on child stderr");
-
-        if let SignalOrStatus::Signal(signal) = output.result {
-            write!(
-                child_stderr,
-                "\nProcess was possibly killed by signal {}",
-                signal.as_str()
-            )
-            .expect("Formatting string should never fail")
-        }
+            .expect("Task panicked")?;
+        tracing::trace!("Finished waiting on child stderr");
 
-        println!("Starting to wait on child stdout");
+        tracing::trace!("Starting to wait on child stdout");
         let stdout = output
             .outputs
             .remove(&STDOUT_FILENO)
             .expect("Expected an STDOUT pipe to exist")
             .await
-            .expect("Task panicked")?
-            .into_string()?;
-        println!("Finished waiting on child stdout");
+            .expect("Task panicked")?;
+        tracing::trace!("Finished waiting on child stdout");
+
+        tracing::debug!(exit_status = ?output.result, "Sandbox finished");
 
         Ok(RunCodeResult {
-            stdout,
-            stderr: child_stderr,
+            output_truncated: stdout.truncated() || child_stderr.truncated(),
+            stdout: stdout.into_string()?,
+            stderr: child_stderr.into_string()?,
             exit_status: match output.result {
                 // Seems signal + 128 is a standard way to report processes that where killed by a signal
                 SignalOrStatus::Signal(e) => (e as i32) + 128,
                 SignalOrStatus::Status(status) => status,
             },
+            signal: match output.result {
+                SignalOrStatus::Signal(e) => Some(e.as_str()),
+                SignalOrStatus::Status(_) => None,
+            },
+            timed_out: false,
         })
     }
 }
@@ -0,0 +1,188 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use common::{
+    ResultDisplay, RunLimits, SelfTestResult,
+    langs::{LANGS, Lang},
+};
+use serde::Deserialize;
+
+use crate::{
+    Message,
+    cachemap::CacheMap,
+    drain,
+    error::{RunLangError, RunProcessError},
+    process_message::process_message,
+};
+
+const SELF_TEST_JUDGE: &str = concat!(
+    "(async function*(context: Context): Challenge {\n",
+    "\tyield (await context.run('self-test input')).assertEquals('Hello, World!\\nself-test input');\n",
+    "\treturn context.noFailures();\n",
+    "})"
+);
+
+#[derive(Deserialize)]
+pub struct SelfTestQuery {
+    lang: Option<String>,
+}
+
+pub async fn self_test_endpoint(
+    State(lang_versions): State<Arc<CacheMap<String, CacheMap<String, ()>>>>,
+    Query(query): Query<SelfTestQuery>,
+) -> Result<Json<Vec<SelfTestResult>>, RunLangError> {
+    let Some(_in_flight) = drain::start_request() else {
+        return Err(RunLangError::Draining);
+    };
+
+    let languages = match query.lang {
+        Some(lang) => vec![
+            LANGS
+                .get_entry(&lang)
+                .map(|(name, _)| *name)
+                .ok_or(RunLangError::RunLang(RunProcessError::NoSuchLanguage))?,
+        ],
+        None => LANGS.keys().copied().collect(),
+    };
+
+    tokio::select! {
+        results = run_self_test(&languages, lang_versions) => Ok(Json(results)),
+        _ = drain::cut_off() => Err(RunLangError::Draining),
+    }
+}
+
+/// Runs the self test program of each language against a built in judge. Languages that
+/// are not installed yet are installed first, so the first run can take a while.
+pub async fn run_self_test(
+    languages: &[&'static str],
+    lang_versions: Arc<CacheMap<String, CacheMap<String, ()>>>,
+) -> Vec<SelfTestResult> {
+    let mut results = futures_util::future::join_all(languages.iter().map(|name| {
+        let lang = LANGS.get(name).expect("Expected the language to exist");
+        self_test_language(name, lang, lang_versions.clone())
+    }))
+    .await;
+    results.sort_by(|a, b| a.language.cmp(&b.language));
+    results
+}
+
+/// Entry point for `lang-runner self-test [language...]`, prints a report and returns
+/// the exit code
+pub async fn self_test_cli(
+    languages: Vec<String>,
+    lang_versions: Arc<CacheMap<String, CacheMap<String, ()>>>,
+) -> i32 {
+    let languages = if languages.is_empty() {
+        LANGS.keys().copied().collect()
+    } else {
+        let mut names = vec![];
+        for language in &languages {
+            let Some((name, _)) = LANGS.get_entry(language) else {
+                eprintln!("Unknown language {language}");
+                return 2;
+            };
+            names.push(*name);
+        }
+        names
+    };
+
+    let results = run_self_test(&languages, lang_versions).await;
+    for result in &results {
+        println!(
+            "{:<10} {:<10} {:<4} {:>6.2}s (compile {:.2?}, run {:.2?}, judge {:.2?})",
+            result.language,
+            result.version,
+            if result.pass { "PASS" } else { "FAIL" },
+            result.runtime,
+            result.timers.compile,
+            result.timers.run,
+            result.timers.judge,
+        );
+        if !result.pass {
+            if result.timed_out {
+                println!("    timed out");
+            }
+            for (label, text) in [
+                ("stdout", Some(&result.stdout)),
+                ("stderr", Some(&result.stderr)),
+                ("error", result.error.as_ref()),
+            ] {
+                if let Some(text) = text.filter(|text| !text.is_empty()) {
+                    println!("    {label}:");
+                    for line in text.lines() {
+                        println!("        {line}");
+                    }
+                }
+            }
+        }
+    }
+
+    if results.iter().all(|result| result.pass) {
+        0
+    } else {
+        1
+    }
+}
+
+async fn self_test_language(
+    name: &'static str,
+    lang: &'static Lang,
+    lang_versions: Arc<CacheMap<String, CacheMap<String, ()>>>,
+) -> SelfTestResult {
+    let mut result = SelfTestResult {
+        language: name.to_owned(),
+        version: lang.latest_version.to_owned(),
+        pass: false,
+        timed_out: false,
+        runtime: 0.0,
+        timers: Default::default(),
+        stdout: String::new(),
+        stderr: String::new(),
+        error: None,
+    };
+
+    if lang.self_test_program.is_empty() {
+        result.error = Some("This language has no self test program".to_owned());
+        return result;
+    }
+
+    let output = match process_message(
+        Message {
+            lang: name.to_owned(),
+            version: lang.latest_version.to_owned(),
+            code: lang.self_test_program.to_owned(),
+            judge: SELF_TEST_JUDGE.to_owned(),
+            binary: false,
+            limits: RunLimits::default(),
+        },
+        lang_versions,
+    )
+    .await
+    {
+        Ok(output) => output,
+        Err(e) => {
+            result.error = Some(format!("{e:?}"));
+            return result;
+        }
+    };
+
+    result.pass = output.tests.pass;
+    result.timed_out = output.timed_out;
+    result.runtime = output.runtime;
+    result.timers = output.timers;
+    if !output.stderr.is_empty() {
+        result.error = Some(output.stderr);
+    }
+
+    for test_case in output.tests.test_cases {
+        if let ResultDisplay::Run { output, error, .. } = test_case.result_display {
+            result.stdout = output;
+            result.stderr = error;
+        }
+    }
+
+    result
+}
@@ -69,7 +69,7 @@ where
         match self.future.poll_unpin(cx) {
             Poll::Ready(r) => {
                 self.subtract_time(poll_start_time);
-                eprintln!(
+                tracing::debug!(
                     "Stopwatch: Process finished normally (time since poll start: {:?})",
                     (Instant::now() - poll_start_time)
                 );
@@ -82,7 +82,7 @@ where
             Poll::Pending => (),
             Poll::Ready(_) => {
                 self.subtract_time(poll_start_time);
-                eprintln!(
+                tracing::info!(
                     "Stopwatch: Process finished with timeout (time since poll: {:?})",
                     (Instant::now() - poll_start_time)
                 );
@@ -0,0 +1,141 @@
+use std::{borrow::Cow, path::Path, process::Stdio, sync::Arc};
+
+use axum::{Json, extract::State};
+use common::{InstalledLanguageVersion, LanguageVersion, UninstallResult, langs::LANGS};
+use tokio::process::Command;
+
+use crate::{
+    cachemap::CacheMap,
+    error::{RunLangError, RunProcessError},
+    install_lang::get_version_directory,
+    process_message::pause_runs,
+};
+
+pub async fn installed_versions_endpoint(
+    State(lang_versions): State<Arc<CacheMap<String, CacheMap<String, ()>>>>,
+) -> Json<Vec<InstalledLanguageVersion>> {
+    let mut installed = vec![];
+
+    for (language, versions) in lang_versions.entries() {
+        let Some(versions) = versions.get() else {
+            continue;
+        };
+
+        for (version, install) in versions.entries() {
+            if !install.initialized() {
+                continue;
+            }
+
+            let disk_usage = match version_disk_usage(&language, &version).await {
+                Ok(disk_usage) => disk_usage,
+                Err(e) => {
+                    eprintln!("Failed to find the disk usage of {language} {version}: {e:?}");
+                    0
+                }
+            };
+
+            installed.push(InstalledLanguageVersion {
+                language: language.clone(),
+                version,
+                disk_usage,
+            });
+        }
+    }
+
+    installed.sort_by(|a, b| (&a.language, &a.version).cmp(&(&b.language, &b.version)));
+    Json(installed)
+}
+
+/// Uninstalls the given versions. Runs are paused while this happens so no judge can be
+/// using a version while it is being removed.
+pub async fn uninstall_endpoint(
+    State(lang_versions): State<Arc<CacheMap<String, CacheMap<String, ()>>>>,
+    Json(versions): Json<Vec<LanguageVersion>>,
+) -> Result<Json<Vec<UninstallResult>>, RunLangError> {
+    let _paused = pause_runs().await.map_err(RunLangError::SemaphoreError)?;
+
+    let mut results = Vec::with_capacity(versions.len());
+    for LanguageVersion { language, version } in versions {
+        let outcome = uninstall_language_version(&lang_versions, &language, &version).await;
+        results.push(UninstallResult {
+            language,
+            version,
+            freed_bytes: *outcome.as_ref().unwrap_or(&0),
+            error: outcome.err().map(Cow::into_owned),
+        });
+    }
+
+    Ok(Json(results))
+}
+
+async fn uninstall_language_version(
+    lang_versions: &CacheMap<String, CacheMap<String, ()>>,
+    language: &str,
+    version: &str,
+) -> Result<u64, Cow<'static, str>> {
+    if LANGS
+        .get(language)
+        .is_some_and(|lang| lang.latest_version == version)
+    {
+        return Err(Cow::Borrowed("Refusing to uninstall the latest version"));
+    }
+
+    let plugin = lang_versions
+        .get_if_present(&language.to_owned())
+        .ok_or(Cow::Borrowed("Language is not installed"))?;
+    let versions = plugin
+        .get()
+        .ok_or(Cow::Borrowed("Language is not installed"))?;
+    if !versions
+        .get_if_present(&version.to_owned())
+        .is_some_and(|install| install.initialized())
+    {
+        return Err(Cow::Borrowed("Version is not installed"));
+    }
+
+    let disk_usage = version_disk_usage(language, version)
+        .await
+        .map_err(|e| Cow::Owned(format!("{e:?}")))?;
+
+    // Removed from the cache first, if uninstalling fails halfway the next run will
+    // install the version again instead of using a broken install
+    versions.remove(&version.to_owned());
+
+    eprintln!("Uninstalling language version {language} {version}");
+    let status = Command::new("asdf")
+        .args(["uninstall", language, version])
+        .stderr(Stdio::inherit())
+        .status()
+        .await
+        .map_err(|e| Cow::Owned(format!("{e:?}")))?;
+    if !status.success() {
+        return Err(Cow::Owned(format!(
+            "{:?}",
+            RunProcessError::NonZeroStatusCode(status.code())
+        )));
+    }
+
+    Ok(disk_usage)
+}
+
+async fn version_disk_usage(language: &str, version: &str) -> Result<u64, RunProcessError> {
+    let directory = get_version_directory(language, version).await?;
+    tokio::task::spawn_blocking(move || directory_size(&directory))
+        .await
+        .map_err(|_| RunProcessError::JoinFail)?
+        .map_err(RunProcessError::IOError)
+}
+
+fn directory_size(path: &Path) -> std::io::Result<u64> {
+    // Symlinks are not followed, they often point outside of the install folder
+    let metadata = std::fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+
+    let mut size = metadata.len();
+    for entry in std::fs::read_dir(path)? {
+        size += directory_size(&entry?.path())?;
+    }
+    Ok(size)
+}
@@ -0,0 +1,242 @@
+use std::time::Duration;
+
+use common::{RunLimits, langs::LANGS, sql_enums::JudgeFormat};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, query, query_as, types::Json};
+use tokio::time::sleep;
+
+use crate::{
+    declarative_judge::runnable_judge,
+    models::solutions::SolutionCode,
+    source_rules::{SourceRules, test_solution_with_rules},
+};
+
+/// How often every sampled solution is run against the judge
+const RUNS_PER_SOLUTION: usize = 5;
+/// The example code is always checked, on top of this many of the best solutions
+const SAMPLED_SOLUTIONS: i64 = 4;
+
+/// How one solution did over all runs of a flakiness check
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FlakinessSample {
+    pub name: String,
+    pub passes: usize,
+    pub runs: usize,
+}
+
+impl FlakinessSample {
+    pub fn is_flaky(&self) -> bool {
+        self.passes != 0 && self.passes != self.runs
+    }
+}
+
+struct FlakinessCheckRequest {
+    id: i32,
+    challenge: i32,
+}
+
+struct JudgeToCheck {
+    judge: String,
+    judge_format: JudgeFormat,
+    example_code: String,
+    source_rules: Json<SourceRules>,
+    run_limits: Json<RunLimits>,
+}
+
+struct SampledSolution {
+    code: String,
+    binary_code: Option<Vec<u8>>,
+    language: String,
+    author_name: String,
+}
+
+static FLAKINESS_CHECK_NOTIFICATION: tokio::sync::Notify = tokio::sync::Notify::const_new();
+
+pub async fn flakiness_check_task(pool: PgPool) {
+    loop {
+        match flakiness_check_task_inner(&pool).await {
+            Ok(()) => (),
+            Err(e) => {
+                eprintln!("Flakiness check task failed: {e:?}");
+
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    }
+}
+
+async fn flakiness_check_task_inner(pool: &PgPool) -> Result<(), sqlx::Error> {
+    loop {
+        let Some(task) = query_as!(
+            FlakinessCheckRequest,
+            r#"
+                SELECT id, challenge
+                FROM flakiness_check_request
+                WHERE NOT processed
+                ORDER BY id DESC
+                LIMIT 1
+            "#
+        )
+        .fetch_optional(pool)
+        .await?
+        else {
+            FLAKINESS_CHECK_NOTIFICATION.notified().await;
+            continue;
+        };
+
+        // Only the latest judge of a challenge is worth checking
+        query!(
+            "UPDATE flakiness_check_request SET processed=true WHERE challenge=$1 AND id<$2 AND NOT processed",
+            task.challenge,
+            task.id
+        )
+        .execute(pool)
+        .await?;
+
+        let report = check_challenge(pool, task.challenge).await?;
+        let flaky_samples = report.iter().filter(|sample| sample.is_flaky()).count();
+        let flakiness = if report.is_empty() {
+            None
+        } else {
+            Some(flaky_samples as f32 / report.len() as f32)
+        };
+
+        eprintln!(
+            "Flakiness check for challenge {}: {flaky_samples} of {} solutions had inconsistent verdicts",
+            task.challenge,
+            report.len()
+        );
+
+        query!(
+            "UPDATE flakiness_check_request SET processed=true, report=$2 WHERE id=$1",
+            task.id,
+            Json(&report) as _
+        )
+        .execute(pool)
+        .await?;
+        query!(
+            "UPDATE challenges SET flakiness=$2, flakiness_checked_at=now() WHERE id=$1",
+            task.challenge,
+            flakiness
+        )
+        .execute(pool)
+        .await?;
+    }
+}
+
+/// Runs the example code and the best solutions against the judge a few times each
+async fn check_challenge(
+    pool: &PgPool,
+    challenge_id: i32,
+) -> Result<Vec<FlakinessSample>, sqlx::Error> {
+    let challenge = query_as!(
+        JudgeToCheck,
+        r#"
+            SELECT
+                judge,
+                judge_format as "judge_format: JudgeFormat",
+                example_code,
+                source_rules as "source_rules: Json<SourceRules>",
+                run_limits as "run_limits: Json<RunLimits>"
+            FROM challenges
+            WHERE id=$1
+        "#,
+        challenge_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let Ok(judge) = runnable_judge(challenge.judge_format, &challenge.judge) else {
+        return Ok(vec![]);
+    };
+
+    let solutions = query_as!(
+        SampledSolution,
+        r#"
+            SELECT
+                solutions.code,
+                solutions.binary_code,
+                solutions.language,
+                accounts.username as author_name
+            FROM solutions
+            INNER JOIN challenges ON solutions.challenge = challenges.id
+            INNER JOIN accounts ON solutions.author = accounts.id
+            WHERE solutions.challenge=$1 AND solutions.valid
+            ORDER BY ranked_points(solutions.points, challenges.score_direction) ASC, solutions.id ASC
+            LIMIT $2
+        "#,
+        challenge_id,
+        SAMPLED_SOLUTIONS
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut samples = vec![(
+        "Example code".to_owned(),
+        SolutionCode::from_columns(challenge.example_code, None),
+        "nodejs".to_owned(),
+    )];
+    samples.extend(solutions.into_iter().map(|solution| {
+        (
+            format!("{}'s {} solution", solution.author_name, solution.language),
+            SolutionCode::from_columns(solution.code, solution.binary_code),
+            solution.language,
+        )
+    }));
+
+    let mut report = vec![];
+    for (name, code, language) in samples {
+        let Some(lang) = LANGS.get(&language) else {
+            continue;
+        };
+
+        let mut sample = FlakinessSample {
+            name,
+            passes: 0,
+            runs: 0,
+        };
+        for _ in 0..RUNS_PER_SOLUTION {
+            match test_solution_with_rules(
+                &challenge.source_rules,
+                &code.runner_code(),
+                &language,
+                lang.latest_version,
+                &judge,
+                code.is_binary(),
+                *challenge.run_limits,
+            )
+            .await
+            {
+                Ok(result) => {
+                    sample.runs += 1;
+                    if result.tests.pass {
+                        sample.passes += 1;
+                    }
+                }
+                Err(err) => eprintln!("{err:?}"),
+            }
+
+            sleep(Duration::from_millis(250)).await;
+        }
+
+        if sample.runs > 0 {
+            report.push(sample);
+        }
+    }
+
+    Ok(report)
+}
+
+/// Checks the judge of a challenge for inconsistent verdicts in the background
+pub async fn queue_flakiness_check(pool: &PgPool, challenge_id: i32) -> Result<(), sqlx::Error> {
+    query!(
+        "INSERT INTO flakiness_check_request(challenge) VALUES ($1)",
+        challenge_id
+    )
+    .execute(pool)
+    .await?;
+
+    FLAKINESS_CHECK_NOTIFICATION.notify_one();
+
+    Ok(())
+}
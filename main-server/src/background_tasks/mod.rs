@@ -1,3 +1,4 @@
 pub mod announce_ended_challenges;
+pub mod flakiness_check;
 pub mod refresh_views;
 pub mod solution_invalidation;
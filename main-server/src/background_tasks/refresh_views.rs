@@ -1,56 +1,56 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use sqlx::{PgPool, query};
+use sqlx::{PgPool, postgres::PgQueryResult, query};
 
-use crate::{achievements::award_achievements, controllers::challenges::handle_reactions};
+use crate::{
+    achievements::award_achievements, controllers::challenges::handle_reactions,
+    metrics::record_view_refresh,
+};
+
+async fn refresh_view(
+    view: &'static str,
+    statement: impl Future<Output = Result<PgQueryResult, sqlx::Error>>,
+) {
+    let start_time = Instant::now();
+    let result = statement.await;
+    record_view_refresh(view, result.is_ok(), start_time.elapsed());
+
+    if let Err(e) = result {
+        eprintln!("Error refreshing {view}: {e:?}");
+    }
+}
 
 pub async fn refresh_views_task(pool: PgPool) {
     loop {
-        let statement = query!("REFRESH MATERIALIZED VIEW CONCURRENTLY scores")
-            .execute(&pool)
-            .await;
-        match statement {
-            Ok(_) => (),
-            Err(e) => {
-                eprintln!("Error refreshing scores: {e:?}");
-            }
-        }
+        refresh_view(
+            "scores",
+            query!("REFRESH MATERIALIZED VIEW CONCURRENTLY scores").execute(&pool),
+        )
+        .await;
 
         tokio::time::sleep(Duration::from_secs(15)).await;
 
-        let statement = query!("REFRESH MATERIALIZED VIEW CONCURRENTLY user_scoring_info")
-            .execute(&pool)
-            .await;
-        match statement {
-            Ok(_) => (),
-            Err(e) => {
-                eprintln!("Error refreshing scores: {e:?}");
-            }
-        }
+        refresh_view(
+            "user_scoring_info",
+            query!("REFRESH MATERIALIZED VIEW CONCURRENTLY user_scoring_info").execute(&pool),
+        )
+        .await;
 
         tokio::time::sleep(Duration::from_secs(15)).await;
 
-        let statement =
+        refresh_view(
+            "user_scoring_info_per_language",
             query!("REFRESH MATERIALIZED VIEW CONCURRENTLY user_scoring_info_per_language")
-                .execute(&pool)
-                .await;
-        match statement {
-            Ok(_) => (),
-            Err(e) => {
-                eprintln!("Error refreshing scores: {e:?}");
-            }
-        }
+                .execute(&pool),
+        )
+        .await;
 
         // This one is not concurrent since the number of rows should be small
-        let statement = query!("REFRESH MATERIALIZED VIEW achievement_stats")
-            .execute(&pool)
-            .await;
-        match statement {
-            Ok(_) => (),
-            Err(e) => {
-                eprintln!("Error refreshing scores: {e:?}");
-            }
-        }
+        refresh_view(
+            "achievement_stats",
+            query!("REFRESH MATERIALIZED VIEW achievement_stats").execute(&pool),
+        )
+        .await;
 
         tokio::time::sleep(Duration::from_secs(15)).await;
 
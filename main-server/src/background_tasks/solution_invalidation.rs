@@ -1,17 +1,36 @@
 use std::time::Duration;
 
-use common::langs::LANGS;
-use sqlx::{PgPool, query, query_as, query_scalar};
+use common::{
+    RunLimits,
+    langs::LANGS,
+    sql_enums::{JudgeFormat, ScoringUnit},
+};
+use sqlx::{PgPool, query, query_as, query_scalar, types::Json};
 use tokio::time::sleep;
 use tower_sessions::cookie::time::OffsetDateTime;
 
-use crate::{achievements::award_achievement, test_solution::test_solution};
+use crate::{
+    achievements::award_achievement,
+    declarative_judge::runnable_judge,
+    metrics::{
+        record_invalidation_request_processed, record_invalidation_result,
+        set_invalidation_remaining,
+    },
+    models::solutions::SolutionCode,
+    scoring::{default_points, solution_metrics},
+    source_rules::{SourceRules, test_solution_with_rules},
+};
 
 struct QueueEntry {
     id: i32,
     code: String,
+    binary_code: Option<Vec<u8>>,
     language: String,
     judge: String,
+    judge_format: JudgeFormat,
+    unit: ScoringUnit,
+    source_rules: Json<SourceRules>,
+    run_limits: Json<RunLimits>,
     time_out_count: i32,
 }
 
@@ -62,8 +81,13 @@ async fn solution_invalidation_task_inner(pool: &PgPool) -> Result<(), sqlx::Err
                 SELECT
                     solutions.id,
                     solutions.code,
+                    solutions.binary_code,
                     solutions.language,
                     challenges.judge,
+                    challenges.judge_format as "judge_format: JudgeFormat",
+                    challenges.unit as "unit: ScoringUnit",
+                    challenges.source_rules as "source_rules: Json<SourceRules>",
+                    challenges.run_limits as "run_limits: Json<RunLimits>",
                     solutions.time_out_count
                 FROM solutions
                 INNER JOIN challenges ON solutions.challenge = challenges.id
@@ -87,7 +111,13 @@ async fn solution_invalidation_task_inner(pool: &PgPool) -> Result<(), sqlx::Err
         let mut solutions_failed = 0;
         let mut solutions_timed_out = 0;
 
+        let mut remaining = solutions.len();
+        set_invalidation_remaining(remaining);
+
         for solution in solutions {
+            remaining -= 1;
+            set_invalidation_remaining(remaining);
+
             let Some(lang) = LANGS.get(&solution.language) else {
                 eprintln!(
                     "Skipping solution in non-existant lang {}",
@@ -96,19 +126,34 @@ async fn solution_invalidation_task_inner(pool: &PgPool) -> Result<(), sqlx::Err
                 continue;
             };
             let version = lang.latest_version;
+            let code = SolutionCode::from_columns(solution.code, solution.binary_code);
+            let judge = match runnable_judge(solution.judge_format, &solution.judge) {
+                Ok(judge) => judge,
+                Err(err) => {
+                    eprintln!("Skipping solution with an invalid judge: {err:?}");
+                    continue;
+                }
+            };
 
-            let result =
-                match test_solution(&solution.code, &solution.language, version, &solution.judge)
-                    .await
-                {
-                    Ok(e) => e,
-                    Err(err) => {
-                        eprintln!("{err:?}");
+            let result = match test_solution_with_rules(
+                &solution.source_rules,
+                &code.runner_code(),
+                &solution.language,
+                version,
+                &judge,
+                code.is_binary(),
+                *solution.run_limits,
+            )
+            .await
+            {
+                Ok(e) => e,
+                Err(err) => {
+                    eprintln!("{err:?}");
 
-                        sleep(Duration::from_secs(1)).await;
-                        continue;
-                    }
-                };
+                    sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+            };
 
             if result.timed_out && solution.time_out_count < 3 {
                 query!(
@@ -124,17 +169,25 @@ async fn solution_invalidation_task_inner(pool: &PgPool) -> Result<(), sqlx::Err
                 .await?;
 
                 solutions_timed_out += 1;
+                record_invalidation_result("timed_out");
             } else if result.tests.pass {
+                // Rescored so existing solutions pick up changes to the scoring unit
+                let points = result
+                    .tests
+                    .points
+                    .unwrap_or_else(|| default_points(&code, lang, solution.unit) as i32);
                 query!(
-                    "UPDATE solutions SET validated_at=now(), valid=true, fail_reason=null, runtime=$1, points=COALESCE($2,points) WHERE id=$3",
+                    "UPDATE solutions SET validated_at=now(), valid=true, fail_reason=null, runtime=$1, points=COALESCE($2,points), metrics=$3 WHERE id=$4",
                     result.runtime,
-                    result.tests.points,
+                    Some(points),
+                    Json(solution_metrics(&result)) as _,
                     solution.id
                 )
                 .execute(pool)
                 .await?;
 
-                solutions_passed += 1
+                solutions_passed += 1;
+                record_invalidation_result("passed");
             } else {
                 eprintln!(
                     "Solution {} in {} invalidated at {}",
@@ -152,6 +205,7 @@ async fn solution_invalidation_task_inner(pool: &PgPool) -> Result<(), sqlx::Err
                 .await?;
 
                 solutions_failed += 1;
+                record_invalidation_result("failed");
             }
 
             query!(
@@ -185,6 +239,7 @@ async fn solution_invalidation_task_inner(pool: &PgPool) -> Result<(), sqlx::Err
         )
         .execute(pool)
         .await?;
+        record_invalidation_request_processed();
 
         if solutions_failed > 0 {
             award_achievement_for_solutions_invalidation(pool, task, solutions_failed).await?;
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use axum::Extension;
+use common::{LanguageVersion, SelfTestResult, UninstallResult, langs::LANGS};
+use serde::Serialize;
+use sqlx::{PgPool, query, query_as, types::time::OffsetDateTime};
+
+use crate::{
+    error::Error,
+    models::account::Account,
+    test_solution::{get_installed_versions, run_runner_self_test, uninstall_versions},
+};
+
+fn require_admin(account: &Account) -> Result<(), Error> {
+    if !account.admin {
+        return Err(Error::PermissionDenied("Only admins can access this page"));
+    }
+    Ok(())
+}
+
+/// Only shows the form, the self test installs every language so it only runs on a POST
+pub async fn get_runner_self_test(account: Account) -> Result<Option<Vec<SelfTestResult>>, Error> {
+    require_admin(&account)?;
+
+    Ok(None)
+}
+
+pub async fn post_runner_self_test(account: Account) -> Result<Option<Vec<SelfTestResult>>, Error> {
+    require_admin(&account)?;
+
+    run_runner_self_test().await.map(Some)
+}
+
+#[derive(Serialize)]
+pub struct LanguageVersionUsage {
+    language: String,
+    version: String,
+    disk_usage: u64,
+    solutions: i64,
+    is_latest: bool,
+    unused: bool,
+}
+
+#[derive(Serialize)]
+pub struct LanguageVersionsOutput {
+    versions: Vec<LanguageVersionUsage>,
+    unused_disk_usage: u64,
+    uninstalled: Vec<UninstallResult>,
+}
+
+async fn count_solutions_per_version(
+    pool: &PgPool,
+) -> Result<HashMap<(String, String), i64>, sqlx::Error> {
+    Ok(query!(
+        r#"
+            SELECT language, version, COUNT(*) as "count!"
+            FROM solutions
+            GROUP BY language, version
+        "#
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| ((row.language, row.version), row.count))
+    .collect())
+}
+
+/// Cross references the versions installed on the lang runner with the versions used by
+/// solutions. A version is unused if no solution references it and it is not the latest.
+async fn get_version_usage(pool: &PgPool) -> Result<Vec<LanguageVersionUsage>, Error> {
+    let solution_counts = count_solutions_per_version(pool)
+        .await
+        .map_err(Error::Database)?;
+
+    Ok(get_installed_versions()
+        .await?
+        .into_iter()
+        .map(|installed| {
+            let solutions = solution_counts
+                .get(&(installed.language.clone(), installed.version.clone()))
+                .copied()
+                .unwrap_or_default();
+            let is_latest = LANGS
+                .get(&installed.language)
+                .is_some_and(|lang| lang.latest_version == installed.version);
+
+            LanguageVersionUsage {
+                language: installed.language,
+                version: installed.version,
+                disk_usage: installed.disk_usage,
+                solutions,
+                is_latest,
+                unused: solutions == 0 && !is_latest,
+            }
+        })
+        .collect())
+}
+
+fn language_versions_output(
+    versions: Vec<LanguageVersionUsage>,
+    uninstalled: Vec<UninstallResult>,
+) -> LanguageVersionsOutput {
+    LanguageVersionsOutput {
+        unused_disk_usage: versions
+            .iter()
+            .filter(|version| version.unused)
+            .map(|version| version.disk_usage)
+            .sum(),
+        versions,
+        uninstalled,
+    }
+}
+
+pub async fn get_language_versions(
+    account: Account,
+    Extension(pool): Extension<PgPool>,
+) -> Result<LanguageVersionsOutput, Error> {
+    require_admin(&account)?;
+
+    Ok(language_versions_output(
+        get_version_usage(&pool).await?,
+        vec![],
+    ))
+}
+
+pub async fn uninstall_unused_language_versions(
+    account: Account,
+    Extension(pool): Extension<PgPool>,
+) -> Result<LanguageVersionsOutput, Error> {
+    require_admin(&account)?;
+
+    let unused = get_version_usage(&pool)
+        .await?
+        .into_iter()
+        .filter(|version| version.unused)
+        .map(|version| LanguageVersion {
+            language: version.language,
+            version: version.version,
+        })
+        .collect::<Vec<_>>();
+
+    let uninstalled = if unused.is_empty() {
+        vec![]
+    } else {
+        uninstall_versions(&unused).await?
+    };
+
+    Ok(language_versions_output(
+        get_version_usage(&pool).await?,
+        uninstalled,
+    ))
+}
+
+#[derive(Serialize)]
+pub struct FlakyJudge {
+    id: i32,
+    name: String,
+    flakiness_percent: i32,
+    flakiness_checked_at: Option<OffsetDateTime>,
+}
+
+pub async fn get_flaky_judges(
+    account: Account,
+    Extension(pool): Extension<PgPool>,
+) -> Result<Vec<FlakyJudge>, Error> {
+    require_admin(&account)?;
+
+    query_as!(
+        FlakyJudge,
+        r#"
+            SELECT
+                id,
+                name,
+                CAST(ROUND(flakiness * 100) AS integer) as "flakiness_percent!",
+                flakiness_checked_at
+            FROM challenges
+            WHERE flakiness > 0
+            ORDER BY flakiness DESC, flakiness_checked_at DESC
+        "#
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(Error::Database)
+}
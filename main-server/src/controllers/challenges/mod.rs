@@ -4,18 +4,23 @@ mod view_challenge;
 
 use axum::{Extension, extract::Path, http::StatusCode};
 use common::{
-    sql_enums::{ChallengeCategory, ChallengeStatus},
+    RunLangOutput, RunLimits,
+    sql_enums::{ChallengeCategory, ChallengeStatus, JudgeFormat, ScoreDirection, ScoringUnit},
     urls::get_url_for_challenge,
 };
 use macros::CustomResponseMetadata;
-use serde::Serialize;
-use sqlx::PgPool;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, types::Json};
 
 pub use reactions::{handle_reactions, post_reaction};
 pub use view_challenge::{post_comment, view_challenge};
 
 use crate::{
-    background_tasks::solution_invalidation::queue_solution_retesting,
+    background_tasks::{
+        flakiness_check::queue_flakiness_check,
+        solution_invalidation::queue_solution_retesting,
+    },
+    declarative_judge::{DeclarativeJudge, runnable_judge},
     discord::DiscordEventSender,
     error::Error,
     models::{
@@ -27,8 +32,9 @@ use crate::{
         },
         solutions::InvalidatedSolution,
     },
+    source_rules::{SourceRules, test_solution_with_rules},
     tera_utils::auto_input::AutoInput,
-    test_solution::test_solution,
+    test_case_formatting::OutputDisplay,
 };
 
 async fn do_invalid_solutions_exist(
@@ -44,6 +50,32 @@ async fn do_invalid_solutions_exist(
     })
 }
 
+/// Runs the negative examples like the example code, and returns the first one that passed
+/// the judge together with its 1-based number
+async fn passing_negative_example(
+    source_rules: &SourceRules,
+    negative_examples: &[String],
+    judge: &str,
+    limits: RunLimits,
+) -> Result<Option<(usize, RunLangOutput)>, Error> {
+    for (index, negative_example) in negative_examples.iter().enumerate() {
+        let tests = test_solution_with_rules(
+            source_rules,
+            negative_example,
+            "nodejs",
+            "22.4.0",
+            judge,
+            false,
+            limits,
+        )
+        .await?;
+        if tests.tests.pass {
+            return Ok(Some((index + 1, tests)));
+        }
+    }
+    Ok(None)
+}
+
 #[derive(Serialize)]
 pub struct HomePageChallengesOutput {
     public_challenges: Vec<HomePageChallenge>,
@@ -137,10 +169,13 @@ pub async fn new_challenge(
 
     let challenge = new_challenge.get_new_challenge();
 
-    if let Err(e) = challenge.validate(
-        existing_challenge.as_ref().map(|k| &k.challenge),
-        account.admin,
-    ) {
+    if let Err(e) = challenge
+        .validate(
+            existing_challenge.as_ref().map(|k| &k.challenge),
+            account.admin,
+        )
+        .await
+    {
         return Ok(CustomResponseMetadata::new(ChallengeWithTests {
             challenge: new_challenge,
             tests: None,
@@ -149,11 +184,15 @@ pub async fn new_challenge(
         .with_status(StatusCode::BAD_REQUEST));
     }
 
-    let tests = test_solution(
+    let judge = runnable_judge(challenge.judge_format, &challenge.judge)?;
+    let tests = test_solution_with_rules(
+        &challenge.source_rules,
         &challenge.example_code,
         "nodejs",
         "22.4.0",
-        &challenge.judge,
+        &judge,
+        false,
+        challenge.run_limits,
     )
     .await
     .inspect_err(|e| eprintln!("{e:?}"))
@@ -162,11 +201,11 @@ pub async fn new_challenge(
     if !tests.tests.pass {
         return Ok(CustomResponseMetadata::new(ChallengeWithTests {
             challenge: new_challenge,
-            tests: Some(tests.into()),
+            tests: Some(OutputDisplay::new(tests, Some(account.id))),
             validation: Some(
                 [
-                    ("judge", "The example code did not pass the tests"),
-                    ("example-code", "The example code did not pass the tests"),
+                    ("judge", "The example code did not pass the tests".into()),
+                    ("example-code", "The example code did not pass the tests".into()),
                 ]
                 .into(),
             ),
@@ -174,31 +213,66 @@ pub async fn new_challenge(
         .with_status(StatusCode::BAD_REQUEST));
     }
 
+    if let Some((number, negative_tests)) = passing_negative_example(
+        &challenge.source_rules,
+        &challenge.negative_examples,
+        &judge,
+        challenge.run_limits,
+    )
+    .await
+    .inspect_err(|e| eprintln!("{e:?}"))
+    .map_err(|_| Error::ServerError)?
+    {
+        return Ok(CustomResponseMetadata::new(ChallengeWithTests {
+            challenge: new_challenge,
+            tests: Some(OutputDisplay::new(negative_tests, Some(account.id))),
+            validation: Some(
+                [(
+                    "negative-examples",
+                    format!("Negative example {number} passed the judge, it should fail").into(),
+                )]
+                .into(),
+            ),
+        })
+        .with_status(StatusCode::BAD_REQUEST));
+    }
+
     match (challenge.status, id) {
         (ChallengeStatus::Draft, None) => Ok(CustomResponseMetadata::new(ChallengeWithTests {
             challenge: new_challenge,
-            tests: Some(tests.into()),
+            tests: Some(OutputDisplay::new(tests, Some(account.id))),
             validation: None,
         })),
         (_, None) => {
             let row = sqlx::query_scalar!(
                 r#"
-                INSERT INTO challenges (name, judge, description, example_code, author, status, category, unit)
-                values ($1, $2, $3, $4, $5, $6::challenge_status, $7::challenge_category, $8)
+                INSERT INTO challenges (name, judge, judge_format, description, example_code, negative_examples, author, status, category, unit, score_direction, tiebreaker, tiebreaker_direction, source_rules, run_limits)
+                values ($1, $2, $3::judge_format, $4, $5, $6, $7, $8::challenge_status, $9::challenge_category, $10::scoring_unit, $11::score_direction, $12, $13::score_direction, $14, $15)
                 RETURNING id"#,
                 challenge.name,
                 challenge.judge,
+                challenge.judge_format as JudgeFormat,
                 challenge.description,
                 challenge.example_code,
+                &challenge.negative_examples,
                 account.id,
                 challenge.status as ChallengeStatus,
                 challenge.category as ChallengeCategory,
-                challenge.unit
+                challenge.unit as ScoringUnit,
+                challenge.score_direction as ScoreDirection,
+                challenge.tiebreaker,
+                challenge.tiebreaker_direction as ScoreDirection,
+                Json(&challenge.source_rules) as _,
+                Json(&challenge.run_limits) as _
             )
             .fetch_one(&pool)
             .await
             .map_err(Error::Database)?;
 
+            queue_flakiness_check(&pool, row)
+                .await
+                .map_err(Error::Database)?;
+
             let destination = format!(
                 "{}",
                 get_url_for_challenge(
@@ -234,49 +308,76 @@ pub async fn new_challenge(
                     SET
                         name=$1,
                         judge=$2, 
-                        description=$3, 
-                        example_code=$4, 
-                        status=$5::challenge_status, 
-                        category=$6::challenge_category,
+                        judge_format=$3::judge_format,
+                        description=$4, 
+                        example_code=$5, 
+                        status=$6::challenge_status, 
+                        category=$7::challenge_category,
                         go_live_date=COALESCE(
                             challenges.go_live_date,
                             CASE
-                                WHEN $5::challenge_status='public' THEN now()
+                                WHEN $6::challenge_status='public' THEN now()
                                 ELSE NULL
                             END
                         ),
                         post_mortem_date=COALESCE(
                             challenges.post_mortem_date,
                             CASE
-                                WHEN $5::challenge_status!='public' THEN NULL
-                                WHEN $6::challenge_category='restricted-source' THEN now() + INTERVAL '2 months'
-                                WHEN $6::challenge_category='code-golf' THEN now() + INTERVAL '6 months'
+                                WHEN $6::challenge_status!='public' THEN NULL
+                                WHEN $7::challenge_category='restricted-source' THEN now() + INTERVAL '2 months'
+                                WHEN $7::challenge_category='code-golf' THEN now() + INTERVAL '6 months'
                                 ELSE NULL
                             END
                         ),
-                        unit=$7
+                        unit=$8::scoring_unit,
+                        score_direction=$9::score_direction,
+                        tiebreaker=$10,
+                        tiebreaker_direction=$11::score_direction,
+                        source_rules=$12,
+                        negative_examples=$13,
+                        run_limits=$14
 
-                    WHERE id=$8",
+                    WHERE id=$15",
                     challenge.name,
                     challenge.judge,
+                    challenge.judge_format as JudgeFormat,
                     challenge.description,
                     challenge.example_code,
                     challenge.status as ChallengeStatus,
                     challenge.category as ChallengeCategory,
-                    challenge.unit,
+                    challenge.unit as ScoringUnit,
+                    challenge.score_direction as ScoreDirection,
+                    challenge.tiebreaker,
+                    challenge.tiebreaker_direction as ScoreDirection,
+                    Json(&challenge.source_rules) as _,
+                    &challenge.negative_examples,
+                    Json(&challenge.run_limits) as _,
                     id
                 )
                 .execute(&pool)
                 .await
                 .unwrap();
 
+                let judge_changed = existing_challenge.challenge.challenge.judge != challenge.judge
+                    || existing_challenge.challenge.challenge.judge_format != challenge.judge_format;
+
                 // Tells the solution invalidator task to re-check all solutions
-                if existing_challenge.challenge.challenge.judge != challenge.judge {
+                if judge_changed
+                    || existing_challenge.challenge.challenge.unit != challenge.unit
+                    || existing_challenge.challenge.challenge.source_rules != challenge.source_rules
+                    || existing_challenge.challenge.challenge.run_limits != challenge.run_limits
+                {
                     queue_solution_retesting(&pool, Some(id), None, None, account.id)
                         .await
                         .map_err(Error::Database)?;
                 };
 
+                if judge_changed {
+                    queue_flakiness_check(&pool, id)
+                        .await
+                        .map_err(Error::Database)?;
+                }
+
                 if existing_challenge.challenge.challenge.status != ChallengeStatus::Public
                     && challenge.status == ChallengeStatus::Public
                 {
@@ -288,9 +389,38 @@ pub async fn new_challenge(
 
             Ok(CustomResponseMetadata::new(ChallengeWithTests {
                 challenge: new_challenge,
-                tests: Some(tests.into()),
+                tests: Some(OutputDisplay::new(tests, Some(account.id))),
                 validation: None,
             }))
         }
     }
 }
+
+#[derive(Deserialize)]
+pub struct JudgeToCompile {
+    judge: String,
+}
+
+#[derive(Serialize)]
+pub struct CompiledJudge {
+    judge: Option<String>,
+    error: Option<String>,
+}
+
+/// Converts a declarative judge to TypeScript, so authors can switch formats in the editor
+pub async fn compile_judge(
+    _account: Account,
+    AutoInput(JudgeToCompile { judge }): AutoInput<JudgeToCompile>,
+) -> Result<CustomResponseMetadata<CompiledJudge>, Error> {
+    Ok(match DeclarativeJudge::parse(&judge) {
+        Ok(judge) => CustomResponseMetadata::new(CompiledJudge {
+            judge: Some(judge.to_typescript()),
+            error: None,
+        }),
+        Err(error) => CustomResponseMetadata::new(CompiledJudge {
+            judge: None,
+            error: Some(error),
+        })
+        .with_status(StatusCode::BAD_REQUEST),
+    })
+}
@@ -1,29 +1,45 @@
-use common::AchievementType;
+use common::{
+    AchievementType, JudgeResult, Metrics, ResultDisplay, RunLangOutput, RunLimits, TestCase,
+    TestPassState, Timers, sql_enums::JudgeFormat,
+};
 use macros::CustomResponseMetadata;
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
-use sqlx::{PgPool, query, query_as, query_scalar};
+use sqlx::{PgPool, query, query_as, query_scalar, types::Json};
 
 use crate::{
     achievements::award_achievement,
-    background_tasks::solution_invalidation::queue_solution_retesting, discord::DiscordEventSender,
-    error::Error, test_case_formatting::OutputDisplay, test_solution::test_solution,
+    background_tasks::{
+        flakiness_check::queue_flakiness_check, solution_invalidation::queue_solution_retesting,
+    },
+    declarative_judge::runnable_judge,
+    discord::DiscordEventSender,
+    error::Error,
+    source_rules::{SourceRules, test_solution_with_rules},
+    test_case_formatting::OutputDisplay,
+    test_solution::describe_judge_type_errors,
 };
 
+use super::passing_negative_example;
+
 struct ChallengeFieldsNeededForValidation {
     judge: String,
+    judge_format: JudgeFormat,
     example_code: String,
+    negative_examples: Vec<String>,
+    source_rules: Json<SourceRules>,
+    run_limits: Json<RunLimits>,
 }
 
 impl ChallengeFieldsNeededForValidation {
     async fn get_by_id(pool: &PgPool, id: i32) -> Result<Self, sqlx::Error> {
         query_as!(
             ChallengeFieldsNeededForValidation,
-            "
-            SELECT judge, example_code
+            r#"
+            SELECT judge, judge_format as "judge_format: JudgeFormat", example_code, negative_examples, source_rules as "source_rules: Json<SourceRules>", run_limits as "run_limits: Json<RunLimits>"
             FROM challenges
             WHERE id=$1
-            ",
+            "#,
             id
         )
         .fetch_one(pool)
@@ -116,6 +132,7 @@ impl CommentDiff {
         if let DiffField::Judge = self.field {
             queue_solution_retesting(pool, Some(challenge_id), None, Some(comment_id), author_id)
                 .await?;
+            queue_flakiness_check(pool, challenge_id).await?;
         }
 
         Ok(())
@@ -178,6 +195,44 @@ async fn insert_diff(
     .map(|_| ())
 }
 
+/// Shown instead of running the example code when the suggested judge doesn't type check
+fn type_errors_output(type_errors: String) -> RunLangOutput {
+    RunLangOutput {
+        tests: JudgeResult {
+            pass: false,
+            test_cases: vec![TestCase {
+                name: Some("Type errors in the judge".to_owned()),
+                group: None,
+                pass: TestPassState::Fail,
+                result_display: ResultDisplay::Text(type_errors),
+            }],
+            points: None,
+            metrics: Metrics::new(),
+        },
+        stderr: String::new(),
+        timed_out: false,
+        runtime: 0.0,
+        timers: Timers::default(),
+    }
+}
+
+/// Marks the results of a negative example that passed the judge as failed
+fn negative_example_passed_output(number: usize, mut output: RunLangOutput) -> RunLangOutput {
+    output.tests.pass = false;
+    output.tests.test_cases.insert(
+        0,
+        TestCase {
+            name: Some(format!("Negative example {number}")),
+            group: None,
+            pass: TestPassState::Fail,
+            result_display: ResultDisplay::Text(
+                "This negative example passed the judge, it should fail".to_owned(),
+            ),
+        },
+    );
+    output
+}
+
 pub(super) struct InsertDiffTask<'a> {
     diff: &'a CommentDiff,
     challenge_id: i32,
@@ -214,6 +269,7 @@ pub(super) async fn handle_diff<'a>(
     pool: &PgPool,
     challenge_id: i32,
     diff: &'a CommentDiff,
+    account_id: i32,
 ) -> Result<Result<InsertDiffTask<'a>, CustomResponseMetadata<OutputDisplay>>, Error> {
     if has_pending_diff(pool, challenge_id, diff.field)
         .await
@@ -239,13 +295,48 @@ pub(super) async fn handle_diff<'a>(
                 _ => unreachable!(),
             };
 
-            let test_results = test_solution(
-                &challenge.example_code,
-                "nodejs",
-                "22.4.0",
-                &challenge.judge,
-            )
-            .await?;
+            let type_errors = match (diff.field, challenge.judge_format) {
+                (DiffField::Judge, JudgeFormat::Typescript) => {
+                    describe_judge_type_errors(&challenge.judge).await
+                }
+                _ => None,
+            };
+
+            let test_results = match type_errors {
+                Some(type_errors) => type_errors_output(type_errors),
+                None => {
+                    let judge = runnable_judge(challenge.judge_format, &challenge.judge)?;
+                    let example_results = test_solution_with_rules(
+                        &challenge.source_rules,
+                        &challenge.example_code,
+                        "nodejs",
+                        "22.4.0",
+                        &judge,
+                        false,
+                        *challenge.run_limits,
+                    )
+                    .await?;
+
+                    // A new judge also has to keep rejecting the negative examples
+                    if example_results.tests.pass && diff.field == DiffField::Judge {
+                        match passing_negative_example(
+                            &challenge.source_rules,
+                            &challenge.negative_examples,
+                            &judge,
+                            *challenge.run_limits,
+                        )
+                        .await?
+                        {
+                            Some((number, negative_results)) => {
+                                negative_example_passed_output(number, negative_results)
+                            }
+                            None => example_results,
+                        }
+                    } else {
+                        example_results
+                    }
+                }
+            };
 
             (Some(test_results), previous_value)
         }
@@ -260,9 +351,11 @@ pub(super) async fn handle_diff<'a>(
     if let Some(result) = test_results
         && (result.timed_out || !result.tests.pass)
     {
-        return Ok(Err(
-            CustomResponseMetadata::new(result.into()).with_status(StatusCode::BAD_REQUEST)
-        ));
+        return Ok(Err(CustomResponseMetadata::new(OutputDisplay::new(
+            result,
+            Some(account_id),
+        ))
+        .with_status(StatusCode::BAD_REQUEST)));
     };
 
     Ok(Ok(InsertDiffTask {
@@ -347,7 +347,7 @@ pub async fn post_comment(
             ));
         }
 
-        match handle_diff(&pool, id, diff).await? {
+        match handle_diff(&pool, id, diff, account.id).await? {
             Ok(e) => Some(e),
             Err(d) => return Ok(d),
         }
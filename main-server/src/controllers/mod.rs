@@ -1,8 +1,10 @@
 pub mod achievements;
+pub mod admin;
 pub mod auth;
 pub mod challenges;
 pub mod docs;
 pub mod global_leaderboard;
 pub mod pending_change_suggestions;
 pub mod solution;
+pub mod test_case_fields;
 pub mod user;
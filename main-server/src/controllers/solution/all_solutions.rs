@@ -4,7 +4,7 @@ use axum::{
     Extension,
     extract::{Path, Query},
 };
-use common::langs::LANGS;
+use common::{binary, langs::LANGS};
 use serde::{Serialize, Serializer};
 use sqlx::PgPool;
 use time::OffsetDateTime;
@@ -41,6 +41,9 @@ pub struct AllSolutionsOutput {
     pub(super) leaderboard: Vec<LeaderboardEntry>,
     pub(super) tests: Option<OutputDisplay>,
     pub(super) code: Cow<'static, str>,
+    /// Set instead of `code` when the solution is binary
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) hexdump: Option<String>,
     pub(super) previous_solution_invalid: bool,
     pub(super) language: String,
     pub(super) ranking: RankingMode,
@@ -57,7 +60,7 @@ pub struct AllSolutionsOutput {
 
 pub async fn all_solutions(
     Path((challenge_id, _slug, language_name)): Path<(i32, String, String)>,
-    Query(SolutionQueryParameters { ranking }): Query<SolutionQueryParameters>,
+    Query(SolutionQueryParameters { ranking, sort }): Query<SolutionQueryParameters>,
     account: Option<Account>,
     Extension(pool): Extension<PgPool>,
 ) -> Result<AllSolutionsOutput, Error> {
@@ -67,6 +70,7 @@ pub async fn all_solutions(
         &language_name,
         account.as_ref().map(|e| e.id),
         ranking,
+        sort.as_deref(),
     )
     .await
     .map_err(Error::Database)?;
@@ -88,6 +92,10 @@ pub async fn all_solutions(
         tests: None,
         previous_solution_invalid: code.as_ref().is_some_and(|e| !e.valid),
         last_improved_date: code.as_ref().map(|i| i.last_improved_date),
+        hexdump: code
+            .as_ref()
+            .and_then(|e| e.binary_code.as_deref())
+            .map(binary::hexdump),
         code: match code {
             Some(e) => Cow::Owned(e.code),
             None => Cow::Borrowed(
@@ -13,7 +13,7 @@ use super::SolutionQueryParameters;
 
 pub async fn get_leaderboard(
     Path((challenge_id, _slug, language_name)): Path<(i32, String, String)>,
-    Query(SolutionQueryParameters { ranking }): Query<SolutionQueryParameters>,
+    Query(SolutionQueryParameters { ranking, sort }): Query<SolutionQueryParameters>,
     account: Account,
     Extension(pool): Extension<PgPool>,
 ) -> Result<Vec<LeaderboardEntry>, Error> {
@@ -23,6 +23,7 @@ pub async fn get_leaderboard(
         &language_name,
         Some(account.id),
         ranking,
+        sort.as_deref(),
     )
     .await
     .map_err(Error::Database)?;
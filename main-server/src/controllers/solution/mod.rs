@@ -2,6 +2,7 @@ mod all_solutions;
 mod leaderboard;
 mod new_solution;
 pub mod post_mortem;
+mod test_run;
 
 use std::borrow::Cow;
 
@@ -9,6 +10,7 @@ pub use all_solutions::all_solutions;
 use common::urls::get_url_for_challenge;
 pub use leaderboard::get_leaderboard;
 pub use new_solution::new_solution;
+pub use test_run::test_run;
 
 use axum::{Extension, extract::Path};
 use serde::{Deserialize, Serialize};
@@ -23,6 +25,9 @@ use crate::{
 pub struct SolutionQueryParameters {
     #[serde(default)]
     ranking: RankingMode,
+    /// Orders the leaderboard by a metric, `-` in front of the name reverses the order
+    #[serde(default)]
+    sort: Option<String>,
 }
 
 pub async fn challenge_redirect(
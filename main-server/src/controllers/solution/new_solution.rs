@@ -2,25 +2,33 @@ use std::borrow::Cow;
 
 use axum::{
     Extension,
-    extract::{Path, Query},
+    extract::{FromRequest, Multipart, Path, Query, Request},
+    response::{IntoResponse, Response},
 };
-use common::{RunLangOutput, langs::LANGS};
+use common::{Metrics, RunLangOutput, langs::LANGS};
 use macros::CustomResponseMetadata;
 use reqwest::StatusCode;
-use sqlx::{PgPool, types::time::OffsetDateTime};
+use sqlx::{
+    PgPool,
+    types::{Json, time::OffsetDateTime},
+};
+use tracing::Instrument;
 
 use crate::{
+    declarative_judge::runnable_judge,
     discord::DiscordEventSender,
     error::Error,
     models::{
         GetById,
         account::Account,
         activity_log::save_activity_log,
-        challenge::ChallengeWithAuthorInfo,
-        solutions::{Code, LeaderboardEntry, NewSolution, ScoreInfo},
+        challenge::{ChallengeWithAuthorInfo, NewChallenge},
+        solutions::{Code, CodeEncoding, LeaderboardEntry, NewSolution, ScoreInfo, SolutionCode},
     },
+    scoring::{default_points, solution_metrics},
+    source_rules::test_solution_with_rules,
     tera_utils::auto_input::AutoInput,
-    test_solution::test_solution,
+    test_case_formatting::OutputDisplay,
 };
 
 use super::{
@@ -29,14 +37,16 @@ use super::{
 };
 
 #[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all)]
 async fn insert_new_solution(
     pool: &PgPool,
     language_name: &str,
     version: &str,
     challenge_id: i32,
-    code: &str,
+    code: &SolutionCode,
     account_id: i32,
     new_points: i32,
+    metrics: &Metrics,
     runtime: f32,
     is_post_mortem: bool,
 ) -> Result<i32, Error> {
@@ -46,22 +56,26 @@ async fn insert_new_solution(
             version,
             challenge, 
             code,
+            binary_code,
             author, 
             points, 
             last_improved_date,
             runtime,
-            is_post_mortem
-        ) values ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            is_post_mortem,
+            metrics
+        ) values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
          RETURNING id",
         language_name,
         version,
         challenge_id,
-        code,
+        code.text(),
+        code.bytes(),
         account_id,
         new_points,
         OffsetDateTime::now_utc(),
         runtime,
-        is_post_mortem
+        is_post_mortem,
+        Json(metrics) as _
     )
     .fetch_one(pool)
     .await
@@ -70,32 +84,39 @@ async fn insert_new_solution(
     Ok(result)
 }
 
+#[tracing::instrument(skip_all)]
 async fn update_solution(
     pool: &PgPool,
-    solution: &NewSolution,
+    solution: &SolutionCode,
     new_points: i32,
+    metrics: &Metrics,
     previous_solution_code: &Code,
     runtime: f32,
+    improved: bool,
 ) -> Result<(), Error> {
     let result = sqlx::query!(
         "UPDATE solutions SET 
             code=$1,
-            points=$2,
+            binary_code=$2,
+            points=$3,
             valid=true,
             validated_at=now(),
-            last_improved_date=$3,
-            runtime=$4,
-            is_post_mortem=$5
-        WHERE id=$6",
-        solution.code,
+            last_improved_date=$4,
+            runtime=$5,
+            is_post_mortem=$6,
+            metrics=$7
+        WHERE id=$8",
+        solution.text(),
+        solution.bytes(),
         new_points,
-        if new_points < previous_solution_code.points || !previous_solution_code.valid {
+        if improved || !previous_solution_code.valid {
             OffsetDateTime::now_utc()
         } else {
             previous_solution_code.last_improved_date
         },
         runtime,
         previous_solution_code.is_post_mortem,
+        Json(metrics) as _,
         previous_solution_code.id
     )
     .execute(pool)
@@ -111,15 +132,34 @@ async fn update_solution(
 
 enum ShouldUpdateSolution<'a> {
     CreateNew,
-    Update(&'a Code),
+    /// Whether the new solution ranks strictly better than the previous one
+    Update(&'a Code, bool),
     None,
 }
 
+/// Orders solutions like the leaderboard, by points and then by the tiebreaker. Lower is better,
+/// a missing tiebreaker metric sorts last.
+fn ranking_key(challenge: &NewChallenge, points: i32, metrics: &Metrics) -> (i32, f64) {
+    let tiebreaker = challenge
+        .tiebreaker
+        .as_ref()
+        .and_then(|tiebreaker| metrics.get(tiebreaker))
+        .map_or(f64::INFINITY, |&metric| {
+            challenge.tiebreaker_direction.ranked_metric(metric)
+        });
+    (challenge.score_direction.ranked_points(points), tiebreaker)
+}
+
 async fn should_update_solution<'a>(
     previous_code: &'a Option<Code>,
     challenge: &ChallengeWithAuthorInfo,
     new_score: i32,
+    new_metrics: &Metrics,
 ) -> ShouldUpdateSolution<'a> {
+    let new_key = ranking_key(&challenge.challenge.challenge, new_score, new_metrics);
+    let previous_key =
+        |code: &Code| ranking_key(&challenge.challenge.challenge, code.points, &code.metrics);
+
     match previous_code {
         // If there is no previous solution, of course replace it
         None => ShouldUpdateSolution::CreateNew,
@@ -130,9 +170,9 @@ async fn should_update_solution<'a>(
         Some(w) if
             // Always replace an invalid solution
             !w.valid
-            // Replace a solution if the score is better
-            || w.points >= new_score => {
-                ShouldUpdateSolution::Update(w)
+            // Replace a solution if the score and tiebreaker are not worse
+            || new_key <= previous_key(w) => {
+                ShouldUpdateSolution::Update(w, new_key < previous_key(w))
         }
         Some(_) => {
             // This means the code passed but is not better than the previously saved solution
@@ -170,7 +210,7 @@ async fn new_solution_inner(
     language_name: &str,
     account: &Option<Account>,
     pool: &PgPool,
-    solution: &NewSolution,
+    solution: &SolutionCode,
     bot: &DiscordEventSender,
 ) -> Result<
     (
@@ -183,10 +223,8 @@ async fn new_solution_inner(
     ),
     Error,
 > {
-    let version = LANGS
-        .get(language_name)
-        .ok_or(Error::NotFound)?
-        .latest_version;
+    let lang = LANGS.get(language_name).ok_or(Error::NotFound)?;
+    let version = lang.latest_version;
 
     if let Some(account) = account {
         account.save_preferred_language(pool, language_name).await?;
@@ -197,11 +235,17 @@ async fn new_solution_inner(
         .map_err(Error::Database)?
         .ok_or(Error::NotFound)?;
 
-    let test_result = test_solution(
-        &solution.code,
+    let test_result = test_solution_with_rules(
+        &challenge.challenge.challenge.source_rules,
+        &solution.runner_code(),
         language_name,
         version,
-        &challenge.challenge.challenge.judge,
+        &runnable_judge(
+            challenge.challenge.challenge.judge_format,
+            &challenge.challenge.challenge.judge,
+        )?,
+        solution.is_binary(),
+        challenge.challenge.challenge.run_limits,
     )
     .await?;
 
@@ -236,26 +280,23 @@ async fn new_solution_inner(
         ));
     }
 
-    let new_points = test_result.tests.points.unwrap_or_else(
-        // Currently the web browser turns all line breaks into "\r\n" when a solution
-        // is submitted. This should eventually be fixed in the frontend, but for now
-        // we just replace "\r\n" with "\n" when calculating the score to make it match
-        // the byte counter in the editor.
-        // Related: https://github.com/Byte-Heist/Byte-Heist/issues/34
-        || (solution.code.len() - solution.code.matches("\r\n").count()) as i32,
-    );
+    let new_points = test_result.tests.points.unwrap_or_else(|| {
+        default_points(solution, lang, challenge.challenge.challenge.unit) as i32
+    });
+    let metrics = solution_metrics(&test_result);
 
     let (status, solution_id) =
-        match should_update_solution(&previous_code, &challenge, new_points).await {
+        match should_update_solution(&previous_code, &challenge, new_points, &metrics).await {
             ShouldUpdateSolution::CreateNew => {
                 let solution_id = insert_new_solution(
                     pool,
                     language_name,
                     version,
                     challenge_id,
-                    &solution.code,
+                    solution,
                     account.id,
                     new_points,
+                    &metrics,
                     test_result.runtime,
                     challenge.challenge.is_post_mortem,
                 )
@@ -274,13 +315,15 @@ async fn new_solution_inner(
 
                 (StatusCode::CREATED, solution_id)
             }
-            ShouldUpdateSolution::Update(previous_code) => {
+            ShouldUpdateSolution::Update(previous_code, improved) => {
                 update_solution(
                     pool,
                     solution,
                     new_points,
+                    &metrics,
                     previous_code,
                     test_result.runtime,
+                    improved,
                 )
                 .await?;
 
@@ -338,14 +381,90 @@ async fn new_solution_inner(
     ))
 }
 
+/// A solution submitted through [`AutoInput`], or a multipart form. In a multipart form
+/// the solution can be uploaded as a `file`, which is stored exactly as uploaded.
+pub enum SolutionUpload {
+    Encoded(NewSolution),
+    File(Vec<u8>),
+}
+
+impl SolutionUpload {
+    pub(super) fn decode(self) -> Result<SolutionCode, Error> {
+        match self {
+            SolutionUpload::Encoded(solution) => solution.decode(),
+            SolutionUpload::File(bytes) => Ok(SolutionCode::Binary(bytes)),
+        }
+    }
+
+    async fn from_multipart(mut multipart: Multipart) -> Result<Self, Response> {
+        let mut code = None;
+        let mut encoding = CodeEncoding::default();
+
+        while let Some(field) = multipart
+            .next_field()
+            .await
+            .map_err(IntoResponse::into_response)?
+        {
+            match field.name() {
+                Some("file") => {
+                    let bytes = field.bytes().await.map_err(IntoResponse::into_response)?;
+                    return Ok(SolutionUpload::File(bytes.to_vec()));
+                }
+                Some("code") => {
+                    code = Some(field.text().await.map_err(IntoResponse::into_response)?);
+                }
+                Some("encoding") => {
+                    let text = field.text().await.map_err(IntoResponse::into_response)?;
+                    encoding =
+                        serde_json::from_value(serde_json::Value::String(text)).map_err(|_| {
+                            (StatusCode::BAD_REQUEST, "Unknown encoding").into_response()
+                        })?;
+                }
+                _ => {}
+            }
+        }
+
+        let code = code.ok_or_else(|| {
+            (StatusCode::BAD_REQUEST, "Expected a code or file field").into_response()
+        })?;
+        Ok(SolutionUpload::Encoded(NewSolution { code, encoding }))
+    }
+}
+
+impl<S: Sync + Send> FromRequest<S> for SolutionUpload {
+    type Rejection = Response;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let is_multipart = request
+            .headers()
+            .get("content-type")
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| content_type.starts_with("multipart/form-data"));
+
+        if is_multipart {
+            let multipart = Multipart::from_request(request, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            Self::from_multipart(multipart).await
+        } else {
+            let AutoInput(solution) = AutoInput::from_request(request, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            Ok(SolutionUpload::Encoded(solution))
+        }
+    }
+}
+
 pub async fn new_solution(
     Path((challenge_id, _slug, language_name)): Path<(i32, String, String)>,
-    Query(SolutionQueryParameters { ranking }): Query<SolutionQueryParameters>,
+    Query(SolutionQueryParameters { ranking, .. }): Query<SolutionQueryParameters>,
     account: Option<Account>,
     Extension(pool): Extension<PgPool>,
     Extension(bot): Extension<DiscordEventSender>,
-    AutoInput(solution): AutoInput<NewSolution>,
+    solution: SolutionUpload,
 ) -> Result<CustomResponseMetadata<AllSolutionsOutput>, Error> {
+    let solution = solution.decode()?;
+
     let (status, test_result, challenge, previous_solution_invalid, previous_scores, last_modified) =
         new_solution_inner(
             challenge_id,
@@ -355,6 +474,11 @@ pub async fn new_solution(
             &solution,
             &bot,
         )
+        .instrument(tracing::info_span!(
+            "new_solution",
+            challenge_id,
+            language = %language_name
+        ))
         .await?;
 
     let leaderboard = LeaderboardEntry::get_leaderboard_and_scores_near(
@@ -363,6 +487,7 @@ pub async fn new_solution(
         &language_name,
         account.as_ref().map(|i| i.id),
         ranking,
+        challenge.challenge.challenge.score_direction,
     )
     .await
     .map_err(Error::Database)?;
@@ -370,8 +495,15 @@ pub async fn new_solution(
     Ok(CustomResponseMetadata::new(AllSolutionsOutput {
         challenge,
         leaderboard: leaderboard.leaderboard,
-        tests: Some(test_result.into()),
-        code: Cow::Owned(solution.code),
+        tests: Some(OutputDisplay::new(
+            test_result,
+            account.as_ref().map(|i| i.id),
+        )),
+        hexdump: solution.hexdump(),
+        code: match solution {
+            SolutionCode::Text(code) => Cow::Owned(code),
+            SolutionCode::Binary(_) => Cow::Borrowed(""),
+        },
         language: language_name,
         previous_solution_invalid,
         last_improved_date: last_modified,
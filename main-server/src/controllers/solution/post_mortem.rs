@@ -1,5 +1,6 @@
 use axum::{Extension, extract::Path, response::Redirect};
-use serde::Serialize;
+use common::{binary::hexdump, sql_enums::ScoringUnit};
+use serde::{Serialize, Serializer};
 use sqlx::{PgPool, query_as};
 
 use crate::{
@@ -15,12 +16,18 @@ pub struct PostMortemViewOutput {
     author: i32,
     language: String,
     description: String,
-    unit: String,
+    unit: ScoringUnit,
+}
+
+fn serialize_hexdump<S: Serializer>(value: &Option<Vec<u8>>, s: S) -> Result<S::Ok, S::Error> {
+    value.as_deref().map(hexdump).serialize(s)
 }
 
 #[derive(Serialize)]
 struct PostMortemSolutionView {
     code: Option<String>,
+    #[serde(rename = "hexdump", serialize_with = "serialize_hexdump")]
+    binary_code: Option<Vec<u8>>,
     author_id: i32,
     author_name: String,
     author_avatar: String,
@@ -79,6 +86,7 @@ async fn post_mortem_query(
         PostMortemSolutionView,
         r#"
             SELECT solutions.code,
+                solutions.binary_code,
                 solutions.points,
                 solutions.runtime,
                 solutions.valid,
@@ -88,8 +96,16 @@ async fn post_mortem_query(
                 accounts.avatar as author_avatar
             FROM solutions
                 INNER JOIN accounts ON solutions.author = accounts.id
+                INNER JOIN challenges ON solutions.challenge = challenges.id
             WHERE solutions.challenge=$1 AND solutions.language=$2
-            ORDER BY valid DESC, points ASC
+            ORDER BY
+                solutions.valid DESC,
+                ranked_points(solutions.points, challenges.score_direction) ASC,
+                ranked_metric(
+                    solutions.metrics,
+                    challenges.tiebreaker,
+                    challenges.tiebreaker_direction
+                ) ASC NULLS LAST
         "#,
         challenge_id,
         language
@@ -107,6 +123,7 @@ async fn pre_mortem_query(
         PostMortemSolutionView,
         r#"
             SELECT null as code,
+                null::bytea as binary_code,
                 solutions.points,
                 solutions.runtime,
                 accounts.id as author_id,
@@ -116,8 +133,16 @@ async fn pre_mortem_query(
                 accounts.avatar as author_avatar
             FROM solutions
                 INNER JOIN accounts ON solutions.author = accounts.id
+                INNER JOIN challenges ON solutions.challenge = challenges.id
             WHERE solutions.challenge=$1 AND solutions.language=$2
-            ORDER BY valid DESC, points ASC
+            ORDER BY
+                solutions.valid DESC,
+                ranked_points(solutions.points, challenges.score_direction) ASC,
+                ranked_metric(
+                    solutions.metrics,
+                    challenges.tiebreaker,
+                    challenges.tiebreaker_direction
+                ) ASC NULLS LAST
         "#,
         challenge_id,
         language
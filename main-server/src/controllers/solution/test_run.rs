@@ -0,0 +1,104 @@
+use std::borrow::Cow;
+
+use axum::{
+    Extension,
+    extract::{Path, Query},
+};
+use common::langs::LANGS;
+use macros::CustomResponseMetadata;
+use reqwest::StatusCode;
+use sqlx::PgPool;
+use tracing::Instrument;
+
+use crate::{
+    declarative_judge::runnable_judge,
+    error::Error,
+    models::{
+        GetById,
+        account::Account,
+        challenge::ChallengeWithAuthorInfo,
+        solutions::{Code, LeaderboardEntry, SolutionCode},
+    },
+    source_rules::test_solution_with_rules,
+    test_case_formatting::OutputDisplay,
+};
+
+use super::{
+    SolutionQueryParameters, all_solutions::AllSolutionsOutput, new_solution::SolutionUpload,
+};
+
+/// Runs a solution against the judge without saving it, so partial code can be tested.
+/// Submitting is still the only way to get on the leaderboard.
+pub async fn test_run(
+    Path((challenge_id, _slug, language_name)): Path<(i32, String, String)>,
+    Query(SolutionQueryParameters { ranking, sort }): Query<SolutionQueryParameters>,
+    account: Account,
+    Extension(pool): Extension<PgPool>,
+    solution: SolutionUpload,
+) -> Result<CustomResponseMetadata<AllSolutionsOutput>, Error> {
+    let solution = solution.decode()?;
+    let lang = LANGS.get(&language_name).ok_or(Error::NotFound)?;
+
+    let challenge = ChallengeWithAuthorInfo::get_by_id(&pool, challenge_id)
+        .await
+        .map_err(Error::Database)?
+        .ok_or(Error::NotFound)?;
+
+    account.test_run_rate_limit(&pool).await?;
+
+    let test_result = test_solution_with_rules(
+        &challenge.challenge.challenge.source_rules,
+        &solution.runner_code(),
+        &language_name,
+        lang.latest_version,
+        &runnable_judge(
+            challenge.challenge.challenge.judge_format,
+            &challenge.challenge.challenge.judge,
+        )?,
+        solution.is_binary(),
+        challenge.challenge.challenge.run_limits,
+    )
+    .instrument(tracing::info_span!(
+        "test_run",
+        challenge_id,
+        language = %language_name
+    ))
+    .await?;
+
+    let leaderboard = LeaderboardEntry::get_leaderboard_near(
+        &pool,
+        challenge_id,
+        &language_name,
+        Some(account.id),
+        ranking,
+        sort.as_deref(),
+    )
+    .await
+    .map_err(Error::Database)?;
+    let previous_code =
+        Code::get_best_code_for_user(&pool, account.id, challenge_id, &language_name).await;
+
+    let status = if test_result.tests.pass {
+        StatusCode::OK
+    } else {
+        StatusCode::BAD_REQUEST
+    };
+
+    Ok(CustomResponseMetadata::new(AllSolutionsOutput {
+        challenge,
+        leaderboard,
+        tests: Some(OutputDisplay::new(test_result, Some(account.id))),
+        hexdump: solution.hexdump(),
+        code: match solution {
+            SolutionCode::Text(code) => Cow::Owned(code),
+            SolutionCode::Binary(_) => Cow::Borrowed(""),
+        },
+        language: language_name,
+        previous_solution_invalid: previous_code.as_ref().is_some_and(|e| !e.valid),
+        last_improved_date: previous_code.as_ref().map(|i| i.last_improved_date),
+        ranking,
+        toast: None,
+        account_id: Some(account.id),
+    })
+    .with_status(status))
+}
@@ -0,0 +1,13 @@
+use axum::extract::Path;
+
+use crate::{
+    error::Error,
+    test_case_formatting::field_pages::{FieldPage, get_page},
+};
+
+/// The fields of a large test case that weren't sent with the rest of the results
+pub async fn get_test_case_fields(
+    Path((id, offset)): Path<(String, usize)>,
+) -> Result<FieldPage, Error> {
+    get_page(&id, offset).ok_or(Error::NotFound)
+}
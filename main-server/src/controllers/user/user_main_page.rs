@@ -1,7 +1,10 @@
 use std::borrow::Cow;
 
 use axum::{Extension, extract::Path};
-use common::{slug::Slug, sql_enums::ChallengeCategory};
+use common::{
+    slug::Slug,
+    sql_enums::{ChallengeCategory, ScoringUnit},
+};
 use serde::Serialize;
 use sqlx::{PgPool, query_as, types::time::OffsetDateTime};
 
@@ -16,7 +19,7 @@ pub struct UserPageLeaderboardEntry {
     points: i32,
     challenge_id: i32,
     challenge_name: String,
-    challenge_unit: String,
+    challenge_unit: ScoringUnit,
 }
 
 #[derive(Serialize)]
@@ -226,12 +229,12 @@ pub async fn get_user(
 
     let solutions = query_as!(
         UserPageLeaderboardEntry,
-        "SELECT solutions.language, solutions.points, solutions.challenge as challenge_id, challenges.name as challenge_name, challenges.unit as challenge_unit
+        r#"SELECT solutions.language, solutions.points, solutions.challenge as challenge_id, challenges.name as challenge_name, challenges.unit as "challenge_unit: ScoringUnit"
         FROM solutions
         LEFT JOIN challenges ON challenges.id = solutions.challenge
         WHERE solutions.author=$1
         AND solutions.valid=true
-        AND challenges.status in ('public', 'beta')",
+        AND challenges.status in ('public', 'beta')"#,
         id
     ).fetch_all(&pool).await
     .map_err(Error::Database)?;
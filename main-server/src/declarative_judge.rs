@@ -0,0 +1,343 @@
+use std::borrow::Cow;
+
+use common::sql_enums::JudgeFormat;
+use serde::{Deserialize, Deserializer, Serialize, de::Error as _};
+
+use crate::error::Error;
+
+const MAX_NUMBER_OF_RUNS: usize = 10;
+
+/// A judge for challenges that only need inputs and expected outputs. It is written in
+/// YAML or JSON and compiled to the equivalent TypeScript judge for the lang runner.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct DeclarativeJudge {
+    #[serde(default)]
+    test_cases: Vec<DeclarativeTestCase>,
+    #[serde(default)]
+    filter_cases: Vec<FilterCase>,
+    #[serde(default = "default_separator", deserialize_with = "deserialize_text")]
+    input_separator: String,
+    #[serde(default = "default_separator", deserialize_with = "deserialize_text")]
+    output_separator: String,
+    #[serde(default = "default_number_of_runs")]
+    number_of_runs: usize,
+    #[serde(default = "default_shuffle")]
+    shuffle: bool,
+    #[serde(default)]
+    compare: CompareMode,
+    /// Only used when comparing numerically
+    #[serde(default = "default_tolerance")]
+    tolerance: f64,
+    #[serde(default)]
+    scoring: Scoring,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct DeclarativeTestCase {
+    #[serde(deserialize_with = "deserialize_text")]
+    input: String,
+    #[serde(deserialize_with = "deserialize_text")]
+    output: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct FilterCase {
+    #[serde(deserialize_with = "deserialize_text")]
+    input: String,
+    /// Whether a solution should output this input
+    output: bool,
+}
+
+#[derive(Deserialize, Default, Debug, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+enum CompareMode {
+    Exact,
+    /// Ignores trailing whitespace, the same as `assertEquals`
+    #[default]
+    Trim,
+    IgnoreWhitespace,
+    CaseInsensitive,
+    /// Compares with `numbersClose`, numbers can differ by `tolerance`, relative to the expected
+    /// number if that is larger than 1
+    Numeric,
+}
+
+#[derive(Deserialize, Default, Debug, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+enum Scoring {
+    /// Every test has to pass, solutions are scored by the unit of the challenge
+    #[default]
+    Length,
+    /// Failing tests are allowed, the points are the number of runs where every test case passed.
+    /// A solution has to pass at least one run, so one that passes nothing isn't saved with 0
+    /// points. Needs a challenge where the highest score wins.
+    PassedRuns,
+}
+
+fn default_separator() -> String {
+    "\n".to_owned()
+}
+
+fn default_number_of_runs() -> usize {
+    2
+}
+
+fn default_shuffle() -> bool {
+    true
+}
+
+fn default_tolerance() -> f64 {
+    1e-6
+}
+
+/// YAML turns unquoted scalars like `5`, `1.0`, `0x10` or `true` into numbers and booleans,
+/// which can't be turned back into the text the author wrote, so those have to be quoted
+fn deserialize_text<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Text {
+        String(String),
+        Number(#[allow(unused)] f64),
+        Bool(bool),
+    }
+
+    match Text::deserialize(deserializer)? {
+        Text::String(text) => Ok(text),
+        Text::Number(_) => Err(D::Error::custom(
+            "put quotes around numbers, like \"1.0\", so they are kept as written",
+        )),
+        Text::Bool(value) => Err(D::Error::custom(format!(
+            "put quotes around \"{value}\" so it is read as text"
+        ))),
+    }
+}
+
+fn to_json(value: &impl Serialize) -> String {
+    serde_json::to_string(value).expect("Strings and numbers should always serialize")
+}
+
+impl DeclarativeJudge {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let judge: DeclarativeJudge = serde_yaml::from_str(source).map_err(|e| e.to_string())?;
+
+        if judge.test_cases.is_empty() && judge.filter_cases.is_empty() {
+            return Err("Add at least one test case or filter case".to_owned());
+        }
+        if !(1..=MAX_NUMBER_OF_RUNS).contains(&judge.number_of_runs) {
+            return Err(format!(
+                "number_of_runs should be between 1 and {MAX_NUMBER_OF_RUNS}"
+            ));
+        }
+        if !judge.tolerance.is_finite() || judge.tolerance < 0.0 {
+            return Err("tolerance should be a positive number".to_owned());
+        }
+
+        Ok(judge)
+    }
+
+    /// Whether the points are the passed runs, so more points are better
+    pub fn scores_passed_runs(&self) -> bool {
+        matches!(self.scoring, Scoring::PassedRuns)
+    }
+
+    fn compare_expression(&self) -> String {
+        match self.compare {
+            CompareMode::Exact => "output === expected".to_owned(),
+            CompareMode::Trim => "eqIgnoreTrailingWhitespace(output, expected)".to_owned(),
+            CompareMode::IgnoreWhitespace => {
+                r#"output.replace(/\s+/g, "") === expected.replace(/\s+/g, "")"#.to_owned()
+            }
+            CompareMode::CaseInsensitive => {
+                "eqIgnoreTrailingWhitespace(output.toLowerCase(), expected.toLowerCase())"
+                    .to_owned()
+            }
+            CompareMode::Numeric => {
+                format!("numbersClose(output, expected, {})", self.tolerance)
+            }
+        }
+    }
+
+    /// Numbers are shown with the same tolerance as the verdict, everything else next to the
+    /// inputs
+    fn display_mode(&self) -> String {
+        match self.compare {
+            CompareMode::Numeric => format!("{{ numeric: {{ tolerance: {} }} }}", self.tolerance),
+            _ => "\"test\"".to_owned(),
+        }
+    }
+
+    /// Generates a judge that uses the same helpers a hand written judge would, so
+    /// authors can switch to TypeScript later without changing how the judge behaves
+    pub fn to_typescript(&self) -> String {
+        // Every run yields one test case, so counting them counts the runs
+        let yield_test_case = match self.scoring {
+            Scoring::Length => "\t\tyield testCase;\n",
+            Scoring::PassedRuns => concat!(
+                "\t\tif (testCase.pass === \"Pass\") passedRuns++;\n",
+                "\t\tyield testCase.replaceFailState(\"Warning\");\n"
+            ),
+        };
+        let options = format!(
+            "inputSeparator: {}, numberOfRuns: {}, shuffle: {}",
+            to_json(&self.input_separator),
+            self.number_of_runs,
+            self.shuffle
+        );
+
+        let mut judge = String::from("(async function*(context: Context): Challenge {\n");
+        if let Scoring::PassedRuns = self.scoring {
+            judge.push_str("\tlet passedRuns = 0;\n");
+        }
+
+        if !self.test_cases.is_empty() {
+            let test_cases = self
+                .test_cases
+                .iter()
+                .map(|test_case| (&test_case.input, &test_case.output))
+                .collect::<Vec<_>>();
+            judge.push_str(&format!(
+                "\tconst testCases: [string, string][] = {};\n",
+                to_json(&test_cases)
+            ));
+            judge.push_str(&format!(
+                "\tfor await (const testCase of context.runTestCases(testCases, {{\n\t\t{options},\n\t\toutputSeparator: {},\n\t\tcompareFunction: (output: string, expected: string): boolean => {},\n\t\tdisplayMode: {},\n\t}})) {{\n",
+                to_json(&self.output_separator),
+                self.compare_expression(),
+                self.display_mode()
+            ));
+            judge.push_str(yield_test_case);
+            judge.push_str("\t}\n");
+        }
+
+        if !self.filter_cases.is_empty() {
+            let filter_cases = self
+                .filter_cases
+                .iter()
+                .map(|filter_case| (&filter_case.input, filter_case.output))
+                .collect::<Vec<_>>();
+            judge.push_str(&format!(
+                "\tconst filterCases: [string, boolean][] = {};\n",
+                to_json(&filter_cases)
+            ));
+            judge.push_str(&format!(
+                "\tfor await (const testCase of context.runFilterCases(filterCases, {{ {options} }})) {{\n"
+            ));
+            judge.push_str(yield_test_case);
+            judge.push_str("\t}\n");
+        }
+
+        judge.push_str(match self.scoring {
+            Scoring::Length => "\treturn context.noFailures();\n",
+            Scoring::PassedRuns => "\treturn new FinalVerdict(passedRuns > 0, passedRuns);\n",
+        });
+        judge.push_str("})");
+        judge
+    }
+}
+
+/// The TypeScript judge that the lang runner should run for a challenge
+pub fn runnable_judge(format: JudgeFormat, judge: &str) -> Result<Cow<'_, str>, Error> {
+    match format {
+        JudgeFormat::Typescript => Ok(Cow::Borrowed(judge)),
+        // Declarative judges are validated when the challenge is saved
+        JudgeFormat::Declarative => DeclarativeJudge::parse(judge)
+            .map(|judge| Cow::Owned(judge.to_typescript()))
+            .map_err(|_| Error::BadRequest("The declarative judge of this challenge is invalid")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_output(output: &str) -> Result<String, String> {
+        DeclarativeJudge::parse(&format!(
+            "test_cases:\n  - input: a\n    output: {output}\n"
+        ))
+        .map(|mut judge| judge.test_cases.remove(0).output)
+    }
+
+    #[test]
+    fn quoted_scalars_are_kept_as_written() {
+        for output in ["1.0", "0.10", "1e3", "0x10", "5", "true"] {
+            assert_eq!(
+                parse_output(&format!("\"{output}\"")).as_deref(),
+                Ok(output)
+            );
+            assert_eq!(parse_output(&format!("'{output}'")).as_deref(), Ok(output));
+        }
+    }
+
+    #[test]
+    fn unquoted_numbers_and_booleans_are_rejected() {
+        for output in ["1.0", "0.10", "1e3", "0x10", "5", "-3", "true", "false"] {
+            let error = parse_output(output).unwrap_err();
+            assert!(error.contains("put quotes around"), "{output}: {error}");
+        }
+    }
+
+    #[test]
+    fn unquoted_text_is_text() {
+        assert_eq!(parse_output("1 2").as_deref(), Ok("1 2"));
+        assert_eq!(parse_output("hello").as_deref(), Ok("hello"));
+    }
+
+    #[test]
+    fn filter_case_outputs_are_booleans() {
+        let judge =
+            DeclarativeJudge::parse("filter_cases:\n  - input: '12'\n    output: true\n").unwrap();
+        assert_eq!(judge.filter_cases[0].input, "12");
+        assert!(judge.filter_cases[0].output);
+    }
+
+    #[test]
+    fn passed_runs_counts_runs_and_needs_one() {
+        let judge = DeclarativeJudge::parse(
+            "test_cases:\n  - input: a\n    output: b\nfilter_cases:\n  - input: c\n    output: false\nscoring: passed-runs\n",
+        )
+        .unwrap();
+        assert!(judge.scores_passed_runs());
+
+        let typescript = judge.to_typescript();
+        assert!(typescript.contains("\tlet passedRuns = 0;\n"));
+        // Once for the test cases and once for the filter cases
+        assert_eq!(
+            typescript
+                .matches("if (testCase.pass === \"Pass\") passedRuns++;")
+                .count(),
+            2
+        );
+        assert_eq!(
+            typescript
+                .matches("yield testCase.replaceFailState(\"Warning\");")
+                .count(),
+            2
+        );
+        assert!(typescript.ends_with("\treturn new FinalVerdict(passedRuns > 0, passedRuns);\n})"));
+    }
+
+    #[test]
+    fn length_scoring_fails_on_any_failure() {
+        let judge = DeclarativeJudge::parse("test_cases:\n  - input: a\n    output: b\n").unwrap();
+        assert!(!judge.scores_passed_runs());
+
+        let typescript = judge.to_typescript();
+        assert!(!typescript.contains("passedRuns"));
+        assert!(typescript.ends_with("\treturn context.noFailures();\n})"));
+    }
+
+    #[test]
+    fn numeric_compare_uses_numbers_close() {
+        let judge = DeclarativeJudge::parse(
+            "test_cases:\n  - input: a\n    output: '1.5'\ncompare: numeric\ntolerance: 0.01\n",
+        )
+        .unwrap();
+
+        let typescript = judge.to_typescript();
+        assert!(typescript.contains("numbersClose(output, expected, 0.01)"));
+        assert!(typescript.contains("displayMode: { numeric: { tolerance: 0.01 } },"));
+    }
+}
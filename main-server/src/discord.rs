@@ -1,6 +1,10 @@
 use std::time::Duration;
 
-use common::{AchievementType, sql_enums::ChallengeStatus, urls::get_url_for_challenge};
+use common::{
+    AchievementType,
+    sql_enums::{ChallengeStatus, ScoreDirection},
+    urls::get_url_for_challenge,
+};
 use discord_bot::{
     Bot, ScoreImproved,
     change_suggestions::post_change_suggestion,
@@ -63,6 +67,11 @@ impl DiscordEventSender {
     ) -> Result<(), tokio::sync::mpsc::error::SendError<DiscordEvent>> {
         self.0.send(message).await
     }
+
+    /// The number of events waiting to be processed
+    pub fn backlog(&self) -> usize {
+        self.0.max_capacity() - self.0.capacity()
+    }
 }
 
 async fn listen_for_events(
@@ -249,6 +258,7 @@ async fn award_achievements(
     challenge_id: i32,
     top_solution: &Option<LeaderboardEntry>,
     solution: &SolutionWithLanguage,
+    direction: ScoreDirection,
 ) -> Result<(), sqlx::Error> {
     if solution.is_post_mortem {
         award_achievement(
@@ -287,7 +297,7 @@ async fn award_achievements(
 
         if top_solution
             .as_ref()
-            .is_none_or(|e| e.points <= solution.points)
+            .is_none_or(|e| !direction.is_better(solution.points, e.points))
         {
             award_achievement(
                 pool,
@@ -377,7 +387,15 @@ async fn post_updated_score(pool: &PgPool, challenge_id: i32, solution_id: i32,
             }
         };
 
-    if let Err(e) = award_achievements(pool, challenge_id, &top_solution, &solution).await {
+    if let Err(e) = award_achievements(
+        pool,
+        challenge_id,
+        &top_solution,
+        &solution,
+        challenge.challenge.challenge.score_direction,
+    )
+    .await
+    {
         eprintln!("Failed to award achievements: {e:?}");
     }
 
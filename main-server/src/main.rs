@@ -1,10 +1,15 @@
 mod achievements;
 mod background_tasks;
 mod controllers;
+mod declarative_judge;
 mod discord;
 mod error;
+mod metrics;
 mod models;
 mod referrer;
+mod request_id;
+mod scoring;
+mod source_rules;
 mod strip_trailing_slashes;
 mod tera_utils;
 mod test_case_formatting;
@@ -15,21 +20,27 @@ use axum::{
     routing::{get, post},
 };
 use background_tasks::{
-    announce_ended_challenges::announce_ended_challenges_task, refresh_views::refresh_views_task,
+    announce_ended_challenges::announce_ended_challenges_task,
+    flakiness_check::flakiness_check_task, refresh_views::refresh_views_task,
     solution_invalidation::solution_invalidation_task,
 };
 use macros::OutputWrapperFactory;
 use referrer::referrer_layer;
+use request_id::request_id_layer;
 use tera_utils::TeraHtmlRenderer;
 use tower_sessions::session_store::ExpiredDeletion;
 
 use anyhow::Context;
 use controllers::{
     achievements::{get_achievement, list_achievements},
+    admin::{
+        get_flaky_judges, get_language_versions, get_runner_self_test, post_runner_self_test,
+        uninstall_unused_language_versions,
+    },
     auth::{github_callback, github_login},
     challenges::{
-        all_challenges, compose_challenge, get_homepage, new_challenge, post_comment,
-        post_reaction, view_challenge,
+        all_challenges, compile_judge, compose_challenge, get_homepage, new_challenge,
+        post_comment, post_reaction, view_challenge,
     },
     docs::get_doc,
     global_leaderboard::{global_leaderboard, global_leaderboard_per_language},
@@ -38,7 +49,9 @@ use controllers::{
         all_solutions, challenge_redirect, challenge_redirect_no_slug,
         challenge_redirect_with_slug, get_leaderboard, new_solution,
         post_mortem::{post_mortem_view, post_mortem_view_without_language},
+        test_run,
     },
+    test_case_fields::get_test_case_fields,
     user::{get_user, get_user_achievements, redirect_to_user_page},
 };
 use discord::DiscordEventSender;
@@ -61,10 +74,10 @@ async fn main() -> anyhow::Result<()> {
     }
 
     // Setup Tracking Subscriber
-    tracing_subscriber::fmt()
-        .log_internal_errors(true)
-        // .with_span_events(FmtSpan::FULL)
-        .init();
+    let _telemetry = common::telemetry::init_tracing("main-server");
+
+    metrics::install_metrics_recorder();
+    test_solution::check_runner_request_timeout();
 
     // Setup SQLX
     let pool = PgPoolOptions::new()
@@ -91,6 +104,7 @@ async fn main() -> anyhow::Result<()> {
     let discord_bot = init_bot_from_env(&pool);
 
     let _invalidation_task = tokio::task::spawn(solution_invalidation_task(pool.clone()));
+    let _flakiness_check_task = tokio::task::spawn(flakiness_check_task(pool.clone()));
     let _announce_ended_challenges_task = tokio::task::spawn(announce_ended_challenges_task(
         pool.clone(),
         discord_bot.clone(),
@@ -148,6 +162,10 @@ async fn main() -> anyhow::Result<()> {
             get(route_factory.handler("submit_challenge/index.html.jinja", compose_challenge))
                 .post(route_factory.handler("submit_challenge/index.html.jinja", new_challenge)),
         )
+        .route(
+            "/challenge/compile-judge",
+            post(route_factory.handler("", compile_judge)),
+        )
         .route(
             "/challenge/{id}",
             get(route_factory.handler("", challenge_redirect)),
@@ -179,6 +197,10 @@ async fn main() -> anyhow::Result<()> {
             get(route_factory.handler("solve_challenge/index.html.jinja", all_solutions))
                 .post(route_factory.handler("solve_challenge/index.html.jinja", new_solution)),
         )
+        .route(
+            "/challenge/{id}/{slug}/solve/{language}/test",
+            post(route_factory.handler("solve_challenge/index.html.jinja", test_run)),
+        )
         .route(
             "/challenge/{id}/{slug}/solutions",
             get(post_mortem_view_without_language),
@@ -187,6 +209,31 @@ async fn main() -> anyhow::Result<()> {
             "/challenge/{id}/{slug}/solutions/{language}",
             get(route_factory.handler("post_mortem_view.html.jinja", post_mortem_view)),
         )
+        .route(
+            "/admin/self-test",
+            get(route_factory.handler("admin/self_test.html.jinja", get_runner_self_test))
+                .post(route_factory.handler("admin/self_test.html.jinja", post_runner_self_test)),
+        )
+        .route(
+            "/admin/flaky-judges",
+            get(route_factory.handler("admin/flaky_judges.html.jinja", get_flaky_judges)),
+        )
+        .route(
+            "/admin/language-versions",
+            get(route_factory.handler(
+                "admin/language_versions.html.jinja",
+                get_language_versions,
+            ))
+            .post(route_factory.handler(
+                "admin/language_versions.html.jinja",
+                uninstall_unused_language_versions,
+            )),
+        )
+        .route(
+            "/test-case-fields/{id}/{offset}",
+            get(route_factory.handler("", get_test_case_fields)),
+        )
+        .route("/metrics", get(metrics::metrics_endpoint))
         .route("/login/github", get(github_login))
         .route(
             "/callback/github",
@@ -219,6 +266,7 @@ async fn main() -> anyhow::Result<()> {
         .fallback(get(route_factory.handler("", strip_trailing_slashes)))
         .layer(tower_http::catch_panic::CatchPanicLayer::new())
         .layer(middleware::from_fn(referrer_layer))
+        .layer(middleware::from_fn(request_id_layer))
         .layer(Extension(pool))
         .layer(Extension(discord_bot))
         .layer(session_layer);
@@ -0,0 +1,113 @@
+use std::{
+    sync::{LazyLock, OnceLock},
+    time::Duration,
+};
+
+use ::metrics::{counter, gauge, histogram};
+use axum::{
+    Extension,
+    http::{HeaderMap, StatusCode, header::AUTHORIZATION},
+};
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+
+use crate::discord::DiscordEventSender;
+
+/// Buckets in seconds, wide enough to cover both a quick materialized view refresh and a
+/// lang runner request that has to install a language first
+const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+];
+
+static PROMETHEUS_HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+/// Scrapers have to send this as a bearer token, the endpoint is disabled without it
+static METRICS_TOKEN: LazyLock<Option<String>> = LazyLock::new(|| {
+    std::env::var("METRICS_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty())
+});
+
+pub fn install_metrics_recorder() {
+    let handle = PrometheusBuilder::new()
+        .set_buckets(DURATION_BUCKETS)
+        .expect("Expected the list of buckets to not be empty")
+        .install_recorder()
+        .expect("Failed to install the metrics recorder");
+
+    PROMETHEUS_HANDLE
+        .set(handle)
+        .expect("The metrics recorder should only be installed once");
+}
+
+/// Compares every byte, so the time taken doesn't tell how much of the token was right
+fn tokens_match(given: &[u8], expected: &[u8]) -> bool {
+    given.len() == expected.len()
+        && given
+            .iter()
+            .zip(expected)
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+pub async fn metrics_endpoint(
+    headers: HeaderMap,
+    Extension(bot): Extension<DiscordEventSender>,
+) -> Result<String, StatusCode> {
+    let Some(expected) = METRICS_TOKEN.as_deref() else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    let given = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if !given.is_some_and(|given| tokens_match(given.as_bytes(), expected.as_bytes())) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    // The backlog is sampled on scrape rather than on every send so it is always current
+    gauge!("discord_event_queue_backlog").set(bot.backlog() as f64);
+
+    Ok(PROMETHEUS_HANDLE
+        .get()
+        .map(PrometheusHandle::render)
+        .unwrap_or_default())
+}
+
+pub fn record_lang_runner_request(language: &str, outcome: &'static str, elapsed: Duration) {
+    counter!(
+        "lang_runner_requests_total",
+        "language" => language.to_owned(),
+        "outcome" => outcome
+    )
+    .increment(1);
+    histogram!(
+        "lang_runner_request_duration_seconds",
+        "language" => language.to_owned()
+    )
+    .record(elapsed);
+}
+
+pub fn record_lang_runner_retry(language: &str) {
+    counter!("lang_runner_retries_total", "language" => language.to_owned()).increment(1);
+}
+
+pub fn record_view_refresh(view: &'static str, success: bool, elapsed: Duration) {
+    counter!(
+        "materialized_view_refreshes_total",
+        "view" => view,
+        "result" => if success { "success" } else { "failure" }
+    )
+    .increment(1);
+    histogram!("materialized_view_refresh_duration_seconds", "view" => view).record(elapsed);
+}
+
+pub fn set_invalidation_remaining(remaining: usize) {
+    gauge!("solution_invalidation_remaining").set(remaining as f64);
+}
+
+pub fn record_invalidation_result(result: &'static str) {
+    counter!("solution_invalidation_solutions_total", "result" => result).increment(1);
+}
+
+pub fn record_invalidation_request_processed() {
+    counter!("solution_invalidation_requests_total").increment(1);
+}
@@ -14,6 +14,9 @@ use tower_sessions::Session;
 
 use crate::{controllers::auth::ACCOUNT_ID_KEY, error::Error};
 
+/// Test runs don't save anything, but they still use the lang runner
+const MAX_TEST_RUNS_PER_MINUTE: i32 = 10;
+
 #[derive(FromRow, Serialize)]
 pub struct Account {
     pub id: i32,
@@ -72,6 +75,33 @@ impl Account {
 
         Ok(())
     }
+
+    /// Counts the test runs of the last minute, separate from [`Account::rate_limit`]
+    pub async fn test_run_rate_limit(&self, pool: &PgPool) -> Result<(), Error> {
+        let test_runs = sqlx::query_scalar!(
+            "UPDATE accounts SET
+                test_runs_in_window = CASE
+                    WHEN test_run_window_start < NOW() - INTERVAL '1 minute' THEN 1
+                    ELSE test_runs_in_window + 1
+                END,
+                test_run_window_start = CASE
+                    WHEN test_run_window_start < NOW() - INTERVAL '1 minute' THEN NOW()
+                    ELSE test_run_window_start
+                END
+            WHERE id=$1
+            RETURNING test_runs_in_window",
+            self.id
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(Error::Database)?;
+
+        if test_runs > MAX_TEST_RUNS_PER_MINUTE {
+            return Err(Error::RateLimit);
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
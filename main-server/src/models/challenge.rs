@@ -1,10 +1,23 @@
-use std::collections::HashMap;
+use std::{borrow::Cow, collections::HashMap};
 
-use common::sql_enums::{ChallengeCategory, ChallengeDifficulty, ChallengeStatus};
-use serde::{Deserialize, Serialize};
-use sqlx::{PgPool, query_as, types::time::OffsetDateTime};
+use common::{
+    RunLimits,
+    sql_enums::{
+        ChallengeCategory, ChallengeDifficulty, ChallengeStatus, JudgeFormat, ScoreDirection,
+        ScoringUnit,
+    },
+};
+use serde::{Deserialize, Deserializer, Serialize};
+use sqlx::{
+    PgPool, query_as,
+    types::{Json, time::OffsetDateTime},
+};
 
-use crate::{error::Error, test_case_formatting::OutputDisplay};
+use crate::{
+    background_tasks::flakiness_check::FlakinessSample, declarative_judge::DeclarativeJudge,
+    error::Error, source_rules::SourceRules, test_case_formatting::OutputDisplay,
+    test_solution::describe_judge_type_errors,
+};
 
 use super::{GetById, account::Account};
 
@@ -12,52 +25,133 @@ use super::{GetById, account::Account};
 pub struct NewChallenge {
     pub description: String,
     pub judge: String,
+    #[serde(default)]
+    pub judge_format: JudgeFormat,
     pub name: String,
     pub example_code: String,
+    /// Solutions that have to fail the judge, so a judge that accepts them can't be saved
+    #[serde(default, deserialize_with = "deserialize_negative_examples")]
+    pub negative_examples: Vec<String>,
     pub category: ChallengeCategory,
     pub status: ChallengeStatus,
-    pub unit: String,
+    pub unit: ScoringUnit,
+    #[serde(default)]
+    pub score_direction: ScoreDirection,
+    /// The metric that orders solutions with equal points
+    #[serde(default, deserialize_with = "deserialize_tiebreaker")]
+    pub tiebreaker: Option<String>,
+    #[serde(default)]
+    pub tiebreaker_direction: ScoreDirection,
+    #[sqlx(json)]
+    #[serde(flatten)]
+    pub source_rules: SourceRules,
+    #[sqlx(json)]
+    #[serde(flatten)]
+    pub run_limits: RunLimits,
+}
+
+/// Forms submit every negative example in one text, separated by lines that only contain
+/// [`NEGATIVE_EXAMPLE_SEPARATOR`]
+fn deserialize_negative_examples<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Examples {
+        List(Vec<String>),
+        Text(String),
+    }
+
+    let examples = match Examples::deserialize(deserializer)? {
+        Examples::List(list) => list,
+        Examples::Text(text) => text
+            .lines()
+            .collect::<Vec<_>>()
+            .split(|line| line.trim_end() == NEGATIVE_EXAMPLE_SEPARATOR)
+            .map(|lines| lines.join("\n"))
+            .collect(),
+    };
+
+    Ok(examples
+        .into_iter()
+        .filter(|example| !example.trim().is_empty())
+        .collect())
+}
+
+/// Forms submit an empty string when there is no tiebreaker
+fn deserialize_tiebreaker<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?
+        .map(|tiebreaker| tiebreaker.trim().to_owned())
+        .filter(|tiebreaker| !tiebreaker.is_empty()))
 }
 
 impl NewChallenge {
-    pub fn validate(
+    pub async fn validate(
         &self,
         previous: Option<&Challenge>,
         is_admin: bool,
-    ) -> Result<(), HashMap<&'static str, &'static str>> {
+    ) -> Result<(), HashMap<&'static str, Cow<'static, str>>> {
         let mut errors = HashMap::new();
 
         if previous.is_some_and(|e| e.is_post_mortem) {
-            errors.insert("status", "Can't edit an ended challenge");
+            errors.insert("status", "Can't edit an ended challenge".into());
         }
 
         if self.name.is_empty() {
-            errors.insert("name", "name can't be empty");
+            errors.insert("name", "name can't be empty".into());
         }
         if self.description.is_empty() {
-            errors.insert("description", "description can not be empty");
+            errors.insert("description", "description can not be empty".into());
         }
         if self.example_code.is_empty() {
-            errors.insert("example-code", "Please enter some example code that should pass your challenge. This ensures your challenge is possible and that your judge works.");
+            errors.insert("example-code", "Please enter some example code that should pass your challenge. This ensures your challenge is possible and that your judge works.".into());
+        }
+        if self.negative_examples.len() > MAX_NEGATIVE_EXAMPLES {
+            errors.insert(
+                "negative-examples",
+                "A challenge can have at most 5 negative examples".into(),
+            );
         }
         if self.judge.is_empty() || self.judge == DEFAULT_JUDGE {
             errors.insert(
                 "judge",
-                "Please write a judge that can validate the correctness of a solution.",
+                "Please write a judge that can validate the correctness of a solution.".into(),
             );
+        } else if self.judge_format == JudgeFormat::Declarative {
+            match DeclarativeJudge::parse(&self.judge) {
+                Err(e) => {
+                    errors.insert("judge", e.into());
+                }
+                Ok(judge)
+                    if judge.scores_passed_runs()
+                        && self.score_direction != ScoreDirection::Maximize =>
+                {
+                    errors.insert(
+                        "judge",
+                        "scoring: passed-runs gives more points for passing more runs, set the winning score to \"Highest\"".into(),
+                    );
+                }
+                Ok(_) => {}
+            }
+        } else if self.judge_format == JudgeFormat::Typescript
+            && let Some(type_errors) = describe_judge_type_errors(&self.judge).await
+        {
+            errors.insert("judge", type_errors.into());
         }
         if self.status == ChallengeStatus::Public
             && !is_admin
             && previous.is_none_or(|k| k.challenge.status == ChallengeStatus::Public)
         {
-            errors.insert("status", "you can't make a challenge public");
+            errors.insert("status", "you can't make a challenge public".into());
         } else if self.status != ChallengeStatus::Public
             && !is_admin
             && previous.is_some_and(|k| k.challenge.status == ChallengeStatus::Public)
         {
             errors.insert(
                 "status",
-                "You can't make a published challenge private again",
+                "You can't make a published challenge private again".into(),
             );
         }
 
@@ -67,7 +161,27 @@ impl NewChallenge {
                     && k.challenge.category != self.category
             })
         {
-            errors.insert("category", "can't change the category of a live challenge");
+            errors.insert(
+                "category",
+                "can't change the category of a live challenge".into(),
+            );
+        }
+        if self.tiebreaker.as_ref().is_some_and(|tiebreaker| {
+            tiebreaker.len() > MAX_METRIC_NAME_LENGTH
+                || !tiebreaker
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        }) {
+            errors.insert(
+                "tiebreaker",
+                "The tiebreaker should be the name of a metric, like runtime".into(),
+            );
+        }
+        if let Err(e) = self.source_rules.validate() {
+            errors.insert("source_rules", e.into());
+        }
+        if let Err(e) = self.run_limits.validate() {
+            errors.insert("run_limits", e.into());
         }
         if errors.is_empty() {
             Ok(())
@@ -77,6 +191,11 @@ impl NewChallenge {
     }
 }
 
+const MAX_METRIC_NAME_LENGTH: usize = 32;
+/// Every negative example is run through the judge each time the challenge is saved
+const MAX_NEGATIVE_EXAMPLES: usize = 5;
+pub const NEGATIVE_EXAMPLE_SEPARATOR: &str = "---";
+
 const DEFAULT_JUDGE: &str = concat!(
     "(async function*(context: Context): Challenge {\n",
     "\t// Single Test\n",
@@ -106,11 +225,18 @@ impl Default for NewChallenge {
             )
             .to_string(),
             judge: DEFAULT_JUDGE.to_string(),
+            judge_format: JudgeFormat::Typescript,
             name: String::new(),
             example_code: String::new(),
+            negative_examples: vec![],
             category: ChallengeCategory::RestrictedSource,
             status: ChallengeStatus::Draft,
-            unit: "bytes".to_string(),
+            unit: ScoringUnit::Bytes,
+            score_direction: ScoreDirection::Minimize,
+            tiebreaker: None,
+            tiebreaker_direction: ScoreDirection::Minimize,
+            source_rules: SourceRules::default(),
+            run_limits: RunLimits::default(),
         }
     }
 }
@@ -148,7 +274,7 @@ pub struct ChallengeWithTests {
     #[serde(flatten)]
     pub challenge: NewOrExistingChallenge,
     pub tests: Option<OutputDisplay>,
-    pub validation: Option<HashMap<&'static str, &'static str>>,
+    pub validation: Option<HashMap<&'static str, Cow<'static, str>>>,
 }
 
 #[derive(sqlx::FromRow, Deserialize, Serialize, Clone)]
@@ -239,6 +365,10 @@ pub struct ChallengeWithAuthorInfo {
     pub challenge: Challenge,
     pub author_name: String,
     pub author_avatar: String,
+    /// The share of sampled solutions that got inconsistent verdicts from the judge
+    pub flakiness: Option<f32>,
+    pub flakiness_checked_at: Option<OffsetDateTime>,
+    pub flakiness_report: Option<Json<Vec<FlakinessSample>>>,
 }
 
 impl GetById for ChallengeWithAuthorInfo {
@@ -248,16 +378,31 @@ impl GetById for ChallengeWithAuthorInfo {
             challenges.name,
             challenges.description,
             challenges.judge,
+            challenges.judge_format,
             challenges.example_code,
+            challenges.negative_examples,
             challenges.author,
             challenges.category,
             challenges.status,
             challenges.post_mortem_date,
             challenges.unit,
+            challenges.score_direction,
+            challenges.tiebreaker,
+            challenges.tiebreaker_direction,
+            challenges.source_rules,
+            challenges.run_limits,
             (challenges.post_mortem_date IS NOT NULL
                 AND challenges.post_mortem_date < now()) as is_post_mortem,
             accounts.username as author_name,
-            accounts.avatar as author_avatar
+            accounts.avatar as author_avatar,
+            challenges.flakiness,
+            challenges.flakiness_checked_at,
+            (
+                SELECT report FROM flakiness_check_request
+                WHERE challenge = challenges.id AND report IS NOT NULL
+                ORDER BY id DESC
+                LIMIT 1
+            ) as flakiness_report
             FROM challenges LEFT JOIN accounts ON challenges.author = accounts.id
             WHERE challenges.id=$1
             "
@@ -1,8 +1,13 @@
+use std::{borrow::Cow, cmp::Ordering};
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+use common::{Metrics, binary, sql_enums::ScoreDirection};
 use serde::{Deserialize, Serialize};
-use sqlx::{PgPool, query_as, query_scalar};
+use sqlx::{PgPool, query_as, query_scalar, types::Json};
 use tower_sessions::cookie::time::OffsetDateTime;
 
 use super::GetById;
+use crate::error::Error;
 
 pub struct SolutionWithLanguage {
     pub points: i32,
@@ -21,15 +26,24 @@ impl SolutionWithLanguage {
             SolutionWithLanguage,
             r#"
                 SELECT DISTINCT ON (language)
-                    points,
-                    is_post_mortem,
-                    language,
-                    author,
+                    solutions.points,
+                    solutions.is_post_mortem,
+                    solutions.language,
+                    solutions.author,
                     accounts.username as author_name
                 FROM solutions
                 LEFT JOIN accounts ON solutions.author = accounts.id
-                WHERE valid AND not is_post_mortem AND challenge=$1
-                ORDER BY language ASC, points ASC, solutions.created_at ASC
+                LEFT JOIN challenges ON solutions.challenge = challenges.id
+                WHERE solutions.valid AND not solutions.is_post_mortem AND solutions.challenge=$1
+                ORDER BY
+                    solutions.language ASC,
+                    ranked_points(solutions.points, challenges.score_direction) ASC,
+                    ranked_metric(
+                        solutions.metrics,
+                        challenges.tiebreaker,
+                        challenges.tiebreaker_direction
+                    ) ASC NULLS LAST,
+                    solutions.created_at ASC
             "#,
             challenge_id
         )
@@ -60,14 +74,91 @@ impl GetById for SolutionWithLanguage {
     }
 }
 
-#[derive(sqlx::FromRow, Deserialize, Serialize)]
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CodeEncoding {
+    #[default]
+    Text,
+    /// Plain hex or the output of `xxd`
+    Hex,
+    Base64,
+}
+
+#[derive(Deserialize)]
 pub struct NewSolution {
     pub code: String,
+    #[serde(default)]
+    pub encoding: CodeEncoding,
+}
+
+impl NewSolution {
+    pub fn decode(self) -> Result<SolutionCode, Error> {
+        match self.encoding {
+            CodeEncoding::Text => Ok(SolutionCode::Text(self.code)),
+            CodeEncoding::Hex => binary::from_hex(&self.code)
+                .map(SolutionCode::Binary)
+                .ok_or(Error::BadRequest("The code is not valid hex")),
+            CodeEncoding::Base64 => BASE64_STANDARD
+                .decode(self.code.trim())
+                .map(SolutionCode::Binary)
+                .map_err(|_| Error::BadRequest("The code is not valid base64")),
+        }
+    }
+}
+
+/// The source of a solution. Binary solutions are stored in the `binary_code` column
+/// because a text column can not hold arbitrary bytes, `code` is left empty for them.
+pub enum SolutionCode {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl SolutionCode {
+    pub fn from_columns(code: String, binary_code: Option<Vec<u8>>) -> Self {
+        match binary_code {
+            Some(bytes) => SolutionCode::Binary(bytes),
+            None => SolutionCode::Text(code),
+        }
+    }
+
+    pub fn is_binary(&self) -> bool {
+        matches!(self, SolutionCode::Binary(_))
+    }
+
+    /// The value for the `code` column
+    pub fn text(&self) -> &str {
+        match self {
+            SolutionCode::Text(code) => code,
+            SolutionCode::Binary(_) => "",
+        }
+    }
+
+    /// The value for the `binary_code` column
+    pub fn bytes(&self) -> Option<&[u8]> {
+        match self {
+            SolutionCode::Text(_) => None,
+            SolutionCode::Binary(bytes) => Some(bytes),
+        }
+    }
+
+    /// The code as it is sent to the lang runner and the judge
+    pub fn runner_code(&self) -> Cow<'_, str> {
+        match self {
+            SolutionCode::Text(code) => Cow::Borrowed(code),
+            SolutionCode::Binary(bytes) => Cow::Owned(binary::to_binary_string(bytes)),
+        }
+    }
+
+    pub fn hexdump(&self) -> Option<String> {
+        self.bytes().map(binary::hexdump)
+    }
 }
 
 #[derive(Serialize)]
 pub struct Code {
     pub code: String,
+    #[serde(skip)]
+    pub binary_code: Option<Vec<u8>>,
     pub points: i32,
     pub id: i32,
     pub valid: bool,
@@ -75,6 +166,8 @@ pub struct Code {
     pub is_post_mortem: bool,
     pub score: Option<i32>,
     pub rank: Option<i64>,
+    #[serde(skip)]
+    pub metrics: Json<Metrics>,
 }
 
 impl Code {
@@ -89,13 +182,15 @@ impl Code {
             r#"
                 SELECT
                     code, 
+                    binary_code,
                     solutions.points,
                     solutions.id,
                     valid,
                     last_improved_date,
                     is_post_mortem as "is_post_mortem!",
                     score,
-                    rank
+                    rank,
+                    solutions.metrics as "metrics: Json<Metrics>"
                 FROM solutions
                 LEFT JOIN scores
                 ON scores.id = solutions.id
@@ -123,6 +218,7 @@ pub struct LeaderboardEntry {
     pub points: i32,
     pub is_post_mortem: bool,
     pub score: Option<i32>,
+    pub metrics: Json<Metrics>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Copy)]
@@ -148,23 +244,28 @@ pub struct LeaderboardNearOutput {
 }
 
 impl LeaderboardEntry {
-    fn calculate_user_score(rank: usize, leaderboard: &[LeaderboardEntry]) -> i32 {
+    fn calculate_user_score(
+        rank: usize,
+        leaderboard: &[LeaderboardEntry],
+        direction: ScoreDirection,
+    ) -> i32 {
+        let points_at = |index: usize| direction.ranked_points(leaderboard[index].points);
         let percentile_50th = if leaderboard.len() > 2 {
-            leaderboard[leaderboard.len() / 2].points
+            points_at(leaderboard.len() / 2)
         } else {
             9999
         };
         let percentile_90th = if leaderboard.len() > 1 {
-            leaderboard[leaderboard.len() * 9 / 10].points
+            points_at(leaderboard.len() * 9 / 10)
         } else {
             9999
         };
         let percentile_10th = if leaderboard.len() > 9 {
-            leaderboard[leaderboard.len() / 10].points
+            points_at(leaderboard.len() / 10)
         } else {
             9999
         };
-        let points = leaderboard[rank - 1].points;
+        let points = points_at(rank - 1);
 
         (if rank == 1 { 10 } else { 0 })
             + (percentile_90th.saturating_sub(points).max(0) / 4).min(50)
@@ -200,15 +301,53 @@ impl LeaderboardEntry {
         }
     }
 
+    /// Orders a leaderboard by one of its metrics instead of by points, a leading `-`
+    /// puts the highest values first. Entries without the metric go last.
+    fn sort_by_metric(leaderboard: &mut [LeaderboardEntry], sort: &str) {
+        let (metric, descending) = match sort.strip_prefix('-') {
+            Some(metric) => (metric, true),
+            None => (sort, false),
+        };
+        let value = |entry: &LeaderboardEntry| {
+            entry
+                .metrics
+                .get(metric)
+                .map(|&value| if descending { -value } else { value })
+        };
+
+        // The sort is stable, so entries with equal values stay ordered by points
+        leaderboard.sort_by(|a, b| match (value(a), value(b)) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+
+        let mut previous = None;
+        let mut rank = 0;
+        for (index, entry) in leaderboard.iter_mut().enumerate() {
+            let current = value(entry);
+            if index == 0 || current != previous {
+                rank = index + 1;
+            }
+            previous = current;
+            entry.rank = rank as i64;
+        }
+    }
+
     pub async fn get_leaderboard_near(
         pool: &PgPool,
         challenge_id: i32,
         language: &str,
         user_id: Option<i32>,
         mode: RankingMode,
+        sort: Option<&str>,
     ) -> Result<Vec<Self>, sqlx::Error> {
-        let leaderboard =
+        let mut leaderboard =
             Self::get_leadeboard_for_challenge_and_language(pool, challenge_id, language).await?;
+        if let Some(sort) = sort {
+            Self::sort_by_metric(&mut leaderboard, sort);
+        }
 
         Ok(Self::truncate_leaderboard(leaderboard, mode, user_id))
     }
@@ -219,14 +358,15 @@ impl LeaderboardEntry {
         language: &str,
         user_id: Option<i32>,
         mode: RankingMode,
+        direction: ScoreDirection,
     ) -> Result<LeaderboardNearOutput, sqlx::Error> {
         let leaderboard =
             Self::get_leadeboard_for_challenge_and_language(pool, challenge_id, language).await?;
         let user_rank = user_id
             .and_then(|user_id| leaderboard.iter().position(|e| e.author_id == user_id))
             .map(|i| i + 1);
-        let user_score =
-            user_rank.map(|user_rank| Self::calculate_user_score(user_rank, &leaderboard));
+        let user_score = user_rank
+            .map(|user_rank| Self::calculate_user_score(user_rank, &leaderboard, direction));
 
         let score_info = user_rank.zip(user_score).map(|(rank, score)| ScoreInfo {
             rank,
@@ -254,14 +394,23 @@ impl LeaderboardEntry {
                 accounts.username as author_name,
                 accounts.avatar as author_avatar,
                 1 as "rank!",
-                points,
+                solutions.points,
                 solutions.is_post_mortem,
-                scores.score as "score:i32"
+                scores.score as "score:i32",
+                solutions.metrics as "metrics: Json<Metrics>"
             FROM solutions
                 LEFT JOIN accounts ON solutions.author = accounts.id
                 LEFT JOIN scores ON scores.id=solutions.id
+                LEFT JOIN challenges ON challenges.id=solutions.challenge
             WHERE solutions.challenge=$1 AND solutions.language=$2 AND valid=true
-            ORDER BY solutions.points ASC, last_improved_date ASC
+            ORDER BY
+                ranked_points(solutions.points, challenges.score_direction) ASC,
+                ranked_metric(
+                    solutions.metrics,
+                    challenges.tiebreaker,
+                    challenges.tiebreaker_direction
+                ) ASC NULLS LAST,
+                solutions.last_improved_date ASC
             LIMIT 1
             "#,
             challenge_id,
@@ -284,15 +433,32 @@ impl LeaderboardEntry {
                 solutions.author as author_id,
                 accounts.username as author_name,
                 accounts.avatar as author_avatar,
-                points,
-                rank() OVER (ORDER BY solutions.points ASC) as "rank!",
+                solutions.points,
+                rank() OVER (
+                    ORDER BY
+                        ranked_points(solutions.points, challenges.score_direction) ASC,
+                        ranked_metric(
+                            solutions.metrics,
+                            challenges.tiebreaker,
+                            challenges.tiebreaker_direction
+                        ) ASC NULLS LAST
+                ) as "rank!",
                 solutions.is_post_mortem,
-                scores.score as "score:i32"
+                scores.score as "score:i32",
+                solutions.metrics as "metrics: Json<Metrics>"
             FROM solutions
                 LEFT JOIN accounts ON solutions.author = accounts.id
                 LEFT JOIN scores ON scores.id=solutions.id
+                LEFT JOIN challenges ON challenges.id=solutions.challenge
             WHERE solutions.challenge=$1 AND solutions.language=$2 AND valid=true
-            ORDER BY solutions.points ASC, last_improved_date ASC
+            ORDER BY
+                ranked_points(solutions.points, challenges.score_direction) ASC,
+                ranked_metric(
+                    solutions.metrics,
+                    challenges.tiebreaker,
+                    challenges.tiebreaker_direction
+                ) ASC NULLS LAST,
+                solutions.last_improved_date ASC
             "#,
             challenge_id,
             language
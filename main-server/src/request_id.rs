@@ -0,0 +1,59 @@
+use axum::{extract::Request, http::HeaderValue, middleware::Next, response::Response};
+use common::telemetry::{REQUEST_ID_HEADER, set_parent_from_headers};
+use tracing::Instrument;
+
+const MAX_REQUEST_ID_LENGTH: usize = 64;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Gives every request an ID and a tracing span. The ID is forwarded to the lang runner
+/// so both sides of a submission can be found in the logs.
+pub async fn request_id_layer(request: Request, next: Next) -> Response {
+    // Keep an ID set by a reverse proxy, as long as it is something sane to put in a log
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| {
+            !value.is_empty()
+                && value.len() <= MAX_REQUEST_ID_LENGTH
+                && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+        .map(ToOwned::to_owned)
+        .unwrap_or_else(new_request_id);
+
+    let span = tracing::info_span!(
+        "request",
+        method = %request.method(),
+        path = request.uri().path(),
+        request_id,
+        status = tracing::field::Empty,
+    );
+    set_parent_from_headers(&span, request.headers());
+
+    let mut response = REQUEST_ID
+        .scope(request_id.clone(), next.run(request))
+        .instrument(span.clone())
+        .await;
+
+    span.record("status", response.status().as_u16());
+    response.headers_mut().insert(
+        REQUEST_ID_HEADER,
+        HeaderValue::from_str(&request_id).expect("Request IDs should be valid header values"),
+    );
+    response
+}
+
+/// The ID of the request currently being handled, or a fresh one when called from a
+/// background task
+pub fn current_request_id() -> String {
+    REQUEST_ID
+        .try_with(Clone::clone)
+        .unwrap_or_else(|_| new_request_id())
+}
+
+fn new_request_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
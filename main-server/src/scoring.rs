@@ -0,0 +1,61 @@
+use common::{Metrics, RunLangOutput, langs::Lang, sql_enums::ScoringUnit};
+use syntect::{parsing::ParseState, util::LinesWithEndings};
+
+use crate::{models::solutions::SolutionCode, tera_utils::syntax_highlighting::with_syntax_set};
+
+/// The points of a solution when the judge does not return any. Binary solutions are
+/// always scored by their length in bytes. `lengthInUnit` in `js/code_editing/code_editor.ts`
+/// mirrors this for the editor counter.
+pub fn default_points(code: &SolutionCode, lang: &Lang, unit: ScoringUnit) -> usize {
+    let code = match code {
+        SolutionCode::Text(code) => code,
+        SolutionCode::Binary(bytes) => return bytes.len(),
+    };
+
+    match unit {
+        ScoringUnit::Bytes | ScoringUnit::Points => lang.byte_count(code),
+        // Browsers submit "\r\n" line breaks, those count as a single character
+        ScoringUnit::Characters => code.chars().count() - code.matches("\r\n").count(),
+        ScoringUnit::Lines => code.lines().count(),
+        ScoringUnit::Tokens => count_tokens(code, lang.syntax_name),
+    }
+}
+
+/// The metrics reported by the judge, plus the `runtime` unless the judge reports its own
+pub fn solution_metrics(output: &RunLangOutput) -> Metrics {
+    let mut metrics = output.tests.metrics.clone();
+    metrics
+        .entry("runtime".to_owned())
+        .or_insert(output.runtime.into());
+    metrics
+}
+
+/// Counts the runs of non whitespace characters that share a syntax highlighting scope,
+/// so `print("a b")` is `print`, `(`, `"`, `a`, `b`, `"` and `)`.
+fn count_tokens(code: &str, syntax_name: &str) -> usize {
+    with_syntax_set(|syntax_set| {
+        let syntax = syntax_set
+            .find_syntax_by_token(syntax_name)
+            .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+        let mut parse_state = ParseState::new(syntax);
+
+        LinesWithEndings::from(code)
+            .map(|line| match parse_state.parse_line(line, syntax_set) {
+                Ok(ops) => {
+                    let mut start = 0;
+                    ops.iter()
+                        .map(|(position, _)| *position)
+                        .chain([line.len()])
+                        .map(|end| {
+                            let count = line[start..end].split_whitespace().count();
+                            start = end;
+                            count
+                        })
+                        .sum()
+                }
+                // Only happens with a broken syntax definition, words are the next best thing
+                Err(_) => line.split_whitespace().count(),
+            })
+            .sum()
+    })
+}
@@ -0,0 +1,233 @@
+use std::collections::BTreeSet;
+
+use common::{
+    JudgeResult, Metrics, ResultDisplay, RunLangOutput, RunLimits, TestCase, TestPassState, Timers,
+};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Deserializer, Serialize, de::Error as _};
+
+use crate::{error::Error, test_solution::test_solution};
+
+const MAX_PATTERNS: usize = 16;
+const MAX_PATTERN_SIZE: usize = 64 * 1024;
+/// Only the first few lines that are too long are reported
+const MAX_LINE_VIOLATIONS: usize = 5;
+
+/// Restricted source rules that are checked by the main server before the judge runs, so
+/// challenge authors don't have to implement them in every judge
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Eq, Debug)]
+#[serde(default)]
+pub struct SourceRules {
+    /// Characters a solution can't contain
+    pub banned_characters: String,
+    /// If not empty, the only characters a solution can contain besides line breaks
+    pub allowed_characters: String,
+    #[serde(deserialize_with = "deserialize_lines")]
+    pub banned_substrings: Vec<String>,
+    /// Regular expressions a solution can't match
+    #[serde(deserialize_with = "deserialize_lines")]
+    pub banned_patterns: Vec<String>,
+    #[serde(deserialize_with = "deserialize_limit")]
+    pub max_distinct_characters: Option<usize>,
+    /// In characters
+    #[serde(deserialize_with = "deserialize_limit")]
+    pub max_line_length: Option<usize>,
+    #[serde(deserialize_with = "deserialize_limit")]
+    pub max_lines: Option<usize>,
+}
+
+/// Forms submit lists as one entry per line
+fn deserialize_lines<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Lines {
+        List(Vec<String>),
+        Text(String),
+    }
+
+    Ok(match Option::<Lines>::deserialize(deserializer)? {
+        Some(Lines::List(list)) => list,
+        Some(Lines::Text(text)) => text
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(ToOwned::to_owned)
+            .collect(),
+        None => vec![],
+    })
+}
+
+/// Forms submit numbers as strings, and an empty string when there is no limit
+fn deserialize_limit<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<usize>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Limit {
+        Number(usize),
+        Text(String),
+    }
+
+    match Option::<Limit>::deserialize(deserializer)? {
+        Some(Limit::Number(limit)) => Ok(Some(limit)),
+        Some(Limit::Text(text)) if text.trim().is_empty() => Ok(None),
+        Some(Limit::Text(text)) => text
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| D::Error::custom("Expected a positive number")),
+        None => Ok(None),
+    }
+}
+
+fn compile_pattern(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern)
+        .size_limit(MAX_PATTERN_SIZE)
+        .build()
+}
+
+fn is_line_break(c: char) -> bool {
+    c == '\n' || c == '\r'
+}
+
+impl SourceRules {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.banned_patterns.len() > MAX_PATTERNS {
+            return Err("A challenge can have at most 16 banned patterns");
+        }
+        if self
+            .banned_patterns
+            .iter()
+            .any(|pattern| compile_pattern(pattern).is_err())
+        {
+            return Err("One of the banned patterns is not a valid regular expression");
+        }
+        Ok(())
+    }
+
+    /// Describes every way the code breaks the rules
+    pub fn violations(&self, code: &str) -> Vec<String> {
+        let mut violations = vec![];
+
+        let banned_characters = code
+            .chars()
+            .filter(|&c| self.banned_characters.contains(c))
+            .collect::<BTreeSet<_>>();
+        if !banned_characters.is_empty() {
+            violations.push(format!(
+                "Contains the banned characters {:?}",
+                banned_characters.into_iter().collect::<String>()
+            ));
+        }
+
+        if !self.allowed_characters.is_empty() {
+            let disallowed_characters = code
+                .chars()
+                .filter(|&c| !is_line_break(c) && !self.allowed_characters.contains(c))
+                .collect::<BTreeSet<_>>();
+            if !disallowed_characters.is_empty() {
+                violations.push(format!(
+                    "Contains characters that are not allowed: {:?}",
+                    disallowed_characters.into_iter().collect::<String>()
+                ));
+            }
+        }
+
+        for substring in &self.banned_substrings {
+            if code.contains(substring.as_str()) {
+                violations.push(format!("Contains the banned substring {substring:?}"));
+            }
+        }
+
+        // Patterns are validated when the challenge is saved
+        for pattern in &self.banned_patterns {
+            if compile_pattern(pattern).is_ok_and(|regex| regex.is_match(code)) {
+                violations.push(format!("Matches the banned pattern /{pattern}/"));
+            }
+        }
+
+        if let Some(max_distinct_characters) = self.max_distinct_characters {
+            let distinct_characters = code
+                .chars()
+                .filter(|&c| c != '\r')
+                .collect::<BTreeSet<_>>()
+                .len();
+            if distinct_characters > max_distinct_characters {
+                violations.push(format!(
+                    "Uses {distinct_characters} distinct characters, at most {max_distinct_characters} are allowed"
+                ));
+            }
+        }
+
+        if let Some(max_line_length) = self.max_line_length {
+            violations.extend(
+                code.lines()
+                    .enumerate()
+                    .map(|(index, line)| (index + 1, line.chars().count()))
+                    .filter(|&(_, length)| length > max_line_length)
+                    .take(MAX_LINE_VIOLATIONS)
+                    .map(|(line_number, length)| {
+                        format!(
+                            "Line {line_number} is {length} characters long, at most {max_line_length} are allowed"
+                        )
+                    }),
+            );
+        }
+
+        if let Some(max_lines) = self.max_lines {
+            let lines = code.lines().count();
+            if lines > max_lines {
+                violations.push(format!(
+                    "Has {lines} lines, at most {max_lines} are allowed"
+                ));
+            }
+        }
+
+        violations
+    }
+
+    /// The output shown instead of the judge's when the code breaks the rules
+    fn violations_output(&self, code: &str) -> Option<RunLangOutput> {
+        let violations = self.violations(code);
+        if violations.is_empty() {
+            return None;
+        }
+
+        Some(RunLangOutput {
+            tests: JudgeResult {
+                pass: false,
+                test_cases: violations
+                    .into_iter()
+                    .map(|violation| TestCase {
+                        name: Some("Source rules".to_owned()),
+                        group: None,
+                        pass: TestPassState::Fail,
+                        result_display: ResultDisplay::Text(violation),
+                    })
+                    .collect(),
+                points: None,
+                metrics: Metrics::new(),
+            },
+            stderr: String::new(),
+            timed_out: false,
+            runtime: 0.0,
+            timers: Timers::default(),
+        })
+    }
+}
+
+/// Runs the judge, unless the code breaks the source rules in which case the lang runner
+/// is never called
+pub async fn test_solution_with_rules(
+    rules: &SourceRules,
+    code: &str,
+    language: &str,
+    version: &str,
+    judge: &str,
+    binary: bool,
+    limits: RunLimits,
+) -> Result<RunLangOutput, Error> {
+    match rules.violations_output(code) {
+        Some(output) => Ok(output),
+        None => test_solution(code, language, version, judge, binary, limits).await,
+    }
+}
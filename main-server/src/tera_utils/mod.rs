@@ -13,7 +13,7 @@ pub mod auto_input;
 mod get_tera;
 mod html_context;
 mod markdown;
-mod syntax_highlighting;
+pub mod syntax_highlighting;
 mod vite;
 
 fn render_html_error(title: &str, error: &tera::Error) -> Response {
@@ -9,6 +9,25 @@ thread_local! {
     static THEME: OnceCell<(syntect::highlighting::Theme, syntect::parsing::SyntaxSet)> = const { OnceCell::new() };
 }
 
+fn with_theme<T>(
+    f: impl FnOnce(&syntect::highlighting::Theme, &syntect::parsing::SyntaxSet) -> T,
+) -> T {
+    THEME.with(|theme| {
+        let (theme, syntax_set) = theme.get_or_init(|| {
+            let themes = syntect::highlighting::ThemeSet::load_defaults();
+            let syntax_set = syntect::parsing::SyntaxSet::load_defaults_newlines();
+
+            (themes.themes[SYNTECT_THEME].clone(), syntax_set)
+        });
+        f(theme, syntax_set)
+    })
+}
+
+/// The syntaxes used for highlighting, shared with token based scoring
+pub fn with_syntax_set<T>(f: impl FnOnce(&syntect::parsing::SyntaxSet) -> T) -> T {
+    with_theme(|_theme, syntax_set| f(syntax_set))
+}
+
 pub struct SyntaxHighight;
 
 impl Filter for SyntaxHighight {
@@ -45,14 +64,7 @@ impl Filter for SyntaxHighight {
             _ => return Err(tera::Error::msg("Expected input of kind string")),
         };
 
-        THEME.with(|theme| -> Result<tera::Value, tera::Error> {
-            let (theme, syntax_set) = theme.get_or_init(|| {
-                let themes = syntect::highlighting::ThemeSet::load_defaults();
-                let syntax_set = syntect::parsing::SyntaxSet::load_defaults_newlines();
-
-                (themes.themes[SYNTECT_THEME].clone(), syntax_set)
-            });
-
+        with_theme(|theme, syntax_set| -> Result<tera::Value, tera::Error> {
             let mut html = highlighted_html_for_string(
                 code,
                 syntax_set,
@@ -1,23 +1,18 @@
-use common::diff_tools::{Columns, Field, FieldKind, get_diff_elements};
+use common::diff_tools::{
+    Columns, Field, FieldKind, get_diff_elements, get_numeric_diff_elements,
+    get_unordered_diff_elements,
+};
 
-pub fn render_diff_display_mode(
-    output: String,
-    expected: String,
-    sep: String,
-    input: Option<String>,
-) -> Columns {
-    let mut diff = get_diff_elements(
-        &output,
-        &expected,
-        &sep,
-        match &input {
-            Some(_) => 1,
-            None => 0,
-        },
-    );
+fn start_column(input: &Option<String>) -> usize {
+    match input {
+        Some(_) => 1,
+        None => 0,
+    }
+}
 
+fn add_input_column(mut diff: Columns, input: Option<String>) -> Columns {
     if let Some(input) = input {
-        diff.column_titles.insert(0, Some("Input"));
+        diff.column_titles.insert(0, Some("Input".into()));
         diff.fields.insert(
             0,
             Field {
@@ -26,9 +21,41 @@ pub fn render_diff_display_mode(
                 content: input,
                 kind: FieldKind::Identical,
                 row_span: diff.height + 1,
+                spans: vec![],
             },
         );
     }
 
     diff
 }
+
+pub fn render_diff_display_mode(
+    output: String,
+    expected: String,
+    sep: String,
+    input: Option<String>,
+) -> Columns {
+    let diff = get_diff_elements(&output, &expected, &sep, start_column(&input));
+    add_input_column(diff, input)
+}
+
+pub fn render_unordered_display_mode(
+    output: String,
+    expected: String,
+    sep: String,
+    input: Option<String>,
+) -> Columns {
+    let diff = get_unordered_diff_elements(&output, &expected, &sep, start_column(&input));
+    add_input_column(diff, input)
+}
+
+pub fn render_numeric_display_mode(
+    output: String,
+    expected: String,
+    sep: String,
+    tolerance: f64,
+    input: Option<String>,
+) -> Columns {
+    let diff = get_numeric_diff_elements(&output, &expected, &sep, tolerance, start_column(&input));
+    add_input_column(diff, input)
+}
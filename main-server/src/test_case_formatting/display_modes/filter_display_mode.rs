@@ -47,6 +47,7 @@ pub fn render_filter_display_mode(
                         column: 0,
                         span: 1,
                         row_span: 1,
+                        spans: vec![],
                         content: input_parts.get(left).copied().unwrap_or("").to_owned(),
                         kind: FieldKind::Identical,
                     },
@@ -54,6 +55,7 @@ pub fn render_filter_display_mode(
                         column: 1,
                         span: 1,
                         row_span: 1,
+                        spans: vec![],
                         content: input_parts.get(left).copied().unwrap_or("").to_owned(),
                         kind: FieldKind::Identical,
                     },
@@ -61,6 +63,7 @@ pub fn render_filter_display_mode(
                         column: 2,
                         span: 1,
                         row_span: 1,
+                        spans: vec![],
                         content: input_parts.get(left).copied().unwrap_or("").to_owned(),
                         kind: FieldKind::Identical,
                     },
@@ -74,6 +77,7 @@ pub fn render_filter_display_mode(
                         column: 1,
                         span: 1,
                         row_span: 1,
+                        spans: vec![],
                         content: left_str.to_owned(),
                         kind: FieldKind::Identical,
                     },
@@ -81,6 +85,7 @@ pub fn render_filter_display_mode(
                         column: 2,
                         span: 1,
                         row_span: 1,
+                        spans: vec![],
                         content: right_str.to_owned(),
                         kind: FieldKind::Identical,
                     },
@@ -94,6 +99,7 @@ pub fn render_filter_display_mode(
                         column: 1,
                         span: 1,
                         row_span: 1,
+                        spans: vec![],
                         content: left_str.to_owned(),
                         kind: FieldKind::Delete,
                     },
@@ -101,6 +107,7 @@ pub fn render_filter_display_mode(
                         column: 2,
                         span: 1,
                         row_span: 1,
+                        spans: vec![],
                         content: right_str.to_owned(),
                         kind: FieldKind::Insert,
                     },
@@ -113,6 +120,7 @@ pub fn render_filter_display_mode(
                     column: 1,
                     span: 1,
                     row_span: 1,
+                    spans: vec![],
                     content: left_str.to_owned(),
                     kind: FieldKind::Delete,
                 }])
@@ -123,6 +131,7 @@ pub fn render_filter_display_mode(
                     column: 2,
                     span: 1,
                     row_span: 1,
+                    spans: vec![],
                     content: right_str.to_owned(),
                     kind: FieldKind::Insert,
                 }])
@@ -135,6 +144,7 @@ pub fn render_filter_display_mode(
                         column: 0,
                         span: 1,
                         row_span: 1,
+                        spans: vec![],
                         content: input_parts.get(left).copied().unwrap_or("").to_owned(),
                         kind: FieldKind::Identical,
                     },
@@ -142,6 +152,7 @@ pub fn render_filter_display_mode(
                         column: 1,
                         span: 1,
                         row_span: 1,
+                        spans: vec![],
                         content: left_str.to_owned(),
                         kind: FieldKind::Delete,
                     },
@@ -155,6 +166,7 @@ pub fn render_filter_display_mode(
                         column: 0,
                         span: 1,
                         row_span: 1,
+                        spans: vec![],
                         content: input_parts.get(right).copied().unwrap_or("").to_owned(),
                         kind: FieldKind::Identical,
                     },
@@ -162,6 +174,7 @@ pub fn render_filter_display_mode(
                         column: 2,
                         span: 1,
                         row_span: 1,
+                        spans: vec![],
                         content: right_str.to_owned(),
                         kind: FieldKind::Insert,
                     },
@@ -178,6 +191,7 @@ pub fn render_filter_display_mode(
                 column: 0,
                 span: 3,
                 row_span: 1,
+                spans: vec![],
                 content: format!("{rows_skipped} identical lines skipped"),
                 kind: FieldKind::Meta,
             }]
@@ -188,7 +202,11 @@ pub fn render_filter_display_mode(
     .collect();
 
     Columns {
-        column_titles: vec![Some("Input"), Some("Output"), Some("Expected")],
+        column_titles: vec![
+            Some("Input".into()),
+            Some("Output".into()),
+            Some("Expected".into()),
+        ],
         height: filtered_fields.len(),
         fields: filtered_fields,
     }
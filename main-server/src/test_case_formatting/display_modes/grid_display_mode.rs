@@ -0,0 +1,134 @@
+use common::diff_tools::{Columns, Field, FieldKind, push_span};
+
+/// Only this many changed rows get their changed cells highlighted, the rest is shown as is
+const MAX_HIGHLIGHTED_ROWS: usize = 100;
+
+/// Rows of characters, without trailing whitespace and trailing empty rows, since those
+/// don't count when comparing outputs
+fn to_grid(text: &str, sep: &str) -> Vec<Vec<char>> {
+    let mut grid: Vec<Vec<char>> = text
+        .split(sep)
+        .map(|row| row.trim_end().chars().collect())
+        .collect();
+    while grid.last().is_some_and(Vec::is_empty) {
+        grid.pop();
+    }
+    grid
+}
+
+fn size(grid: &[Vec<char>]) -> (usize, usize) {
+    (grid.iter().map(Vec::len).max().unwrap_or(0), grid.len())
+}
+
+/// Missing cells count as spaces, a changed one is shown as a highlighted `·` so padding the
+/// other grid needs stands out
+fn render_row(
+    column: usize,
+    row: &[char],
+    other: &[char],
+    kind: FieldKind,
+    highlight: bool,
+) -> Field {
+    let mut spans = vec![];
+    let mut buffer = [0; 4];
+    if highlight {
+        for index in 0..row.len().max(other.len()) {
+            let cell = row.get(index).copied().unwrap_or(' ');
+            let changed = cell != other.get(index).copied().unwrap_or(' ');
+            if index < row.len() || changed {
+                push_span(&mut spans, cell.encode_utf8(&mut buffer), changed);
+            }
+        }
+    }
+
+    Field {
+        column,
+        span: 1,
+        row_span: 1,
+        content: row.iter().collect(),
+        kind,
+        spans,
+    }
+}
+
+pub fn render_grid_display_mode(
+    output: String,
+    expected: String,
+    sep: String,
+    input: Option<String>,
+) -> Columns {
+    let output_grid = to_grid(&output, &sep);
+    let expected_grid = to_grid(&expected, &sep);
+    let start_column = usize::from(input.is_some());
+
+    let mut fields = vec![];
+    let mut height = 0;
+    let mut highlighted_rows = 0;
+    let (output_size, expected_size) = (size(&output_grid), size(&expected_grid));
+    if output_size != expected_size {
+        fields.push(Field {
+            column: start_column,
+            span: 2,
+            row_span: 1,
+            content: format!(
+                "The output is {}×{}, expected {}×{}",
+                output_size.0, output_size.1, expected_size.0, expected_size.1
+            ),
+            kind: FieldKind::Meta,
+            spans: vec![],
+        });
+        height += 1;
+    }
+
+    for index in 0..output_grid.len().max(expected_grid.len()) {
+        let output_row = output_grid.get(index).map_or(&[][..], Vec::as_slice);
+        let expected_row = expected_grid.get(index).map_or(&[][..], Vec::as_slice);
+        let (output_kind, expected_kind) = if output_row == expected_row {
+            (FieldKind::Identical, FieldKind::Identical)
+        } else {
+            (FieldKind::Delete, FieldKind::Insert)
+        };
+        let highlight =
+            output_kind != FieldKind::Identical && highlighted_rows < MAX_HIGHLIGHTED_ROWS;
+        highlighted_rows += usize::from(highlight);
+
+        fields.push(render_row(
+            start_column,
+            output_row,
+            expected_row,
+            output_kind,
+            highlight,
+        ));
+        fields.push(render_row(
+            start_column + 1,
+            expected_row,
+            output_row,
+            expected_kind,
+            highlight,
+        ));
+        height += 1;
+    }
+
+    let mut column_titles = vec![Some("Output".into()), Some("Expected".into())];
+
+    if let Some(input) = input {
+        column_titles.insert(0, Some("Input".into()));
+        fields.insert(
+            0,
+            Field {
+                column: 0,
+                span: 1,
+                row_span: height + 1,
+                content: input,
+                kind: FieldKind::Identical,
+                spans: vec![],
+            },
+        );
+    }
+
+    Columns {
+        column_titles,
+        fields,
+        height,
+    }
+}
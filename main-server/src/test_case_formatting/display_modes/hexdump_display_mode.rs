@@ -0,0 +1,29 @@
+use common::{
+    binary::{from_binary_string, hexdump},
+    diff_tools::{Columns, Field, FieldKind, get_diff_elements},
+};
+
+/// Judges can also give plain text, which is dumped as UTF-8
+fn to_bytes(text: &str) -> Vec<u8> {
+    from_binary_string(text).unwrap_or_else(|| text.as_bytes().to_vec())
+}
+
+pub fn render_hexdump_display_mode(output: String, expected: Option<String>) -> Columns {
+    let output = hexdump(&to_bytes(&output));
+
+    match expected {
+        Some(expected) => get_diff_elements(&output, &hexdump(&to_bytes(&expected)), "\n", 0),
+        None => Columns {
+            column_titles: vec![Some("Output".into())],
+            fields: vec![Field {
+                column: 0,
+                span: 1,
+                content: output,
+                kind: FieldKind::Identical,
+                row_span: 1,
+                spans: vec![],
+            }],
+            height: 1,
+        },
+    }
+}
@@ -0,0 +1,324 @@
+use base64::{Engine, prelude::BASE64_STANDARD};
+use common::{
+    binary::from_binary_string,
+    diff_tools::{Columns, Field, FieldKind},
+};
+
+/// Larger images would make the test case response huge, BMP isn't compressed
+const MAX_IMAGE_DIMENSION: usize = 512;
+
+struct Image {
+    width: usize,
+    height: usize,
+    /// Row by row, from the top left
+    pixels: Vec<[u8; 3]>,
+}
+
+/// Reads the header values and ASCII pixel values of a PNM image, skipping `#` comments
+struct PnmReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl PnmReader<'_> {
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(&byte) = self.data.get(self.position) {
+            if byte == b'#' {
+                while self
+                    .data
+                    .get(self.position)
+                    .is_some_and(|&byte| byte != b'\n')
+                {
+                    self.position += 1;
+                }
+            } else if byte.is_ascii_whitespace() {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<usize, &'static str> {
+        self.skip_whitespace_and_comments();
+        let start = self.position;
+        while self.data.get(self.position).is_some_and(u8::is_ascii_digit) {
+            self.position += 1;
+        }
+        std::str::from_utf8(&self.data[start..self.position])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .ok_or("Expected a number")
+    }
+
+    /// P1 images can leave out the whitespace between pixels
+    fn bit(&mut self) -> Result<bool, &'static str> {
+        self.skip_whitespace_and_comments();
+        let bit = match self.data.get(self.position) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err("Expected a 0 or 1"),
+        };
+        self.position += 1;
+        Ok(bit)
+    }
+
+    /// The raster of binary formats starts after exactly one whitespace character
+    fn raster(&self) -> &[u8] {
+        self.data.get(self.position + 1..).unwrap_or_default()
+    }
+}
+
+/// Supports all PBM, PGM and PPM formats, `P1` to `P6`
+fn parse_pnm(data: &[u8]) -> Result<Image, &'static str> {
+    let format = match data {
+        [b'P', format @ b'1'..=b'6', ..] => *format,
+        _ => return Err("Expected a PBM, PGM or PPM image starting with P1 to P6"),
+    };
+    let mut reader = PnmReader { data, position: 2 };
+
+    let width = reader.number()?;
+    let height = reader.number()?;
+    if width == 0 || height == 0 {
+        return Err("The image is empty");
+    }
+    if width > MAX_IMAGE_DIMENSION || height > MAX_IMAGE_DIMENSION {
+        return Err("The image is larger than 512 by 512 pixels");
+    }
+    let max_value = match format {
+        b'1' | b'4' => 1,
+        _ => reader.number()?,
+    };
+    if !(1..=u16::MAX as usize).contains(&max_value) {
+        return Err("The maximum value should be between 1 and 65535");
+    }
+
+    let channels = match format {
+        b'3' | b'6' => 3,
+        _ => 1,
+    };
+    let samples = width * height * channels;
+    let values: Vec<usize> = match format {
+        // In PBM images 1 is black
+        b'1' => (0..samples)
+            .map(|_| reader.bit().map(|bit| usize::from(!bit)))
+            .collect::<Result<_, _>>()?,
+        b'2' | b'3' => (0..samples)
+            .map(|_| reader.number())
+            .collect::<Result<_, _>>()?,
+        b'4' => {
+            let row_bytes = width.div_ceil(8);
+            let raster = reader.raster();
+            if raster.len() < row_bytes * height {
+                return Err("The image has fewer pixels than its size");
+            }
+            (0..height)
+                .flat_map(|row| (0..width).map(move |column| (row, column)))
+                .map(|(row, column)| {
+                    let byte = raster[row * row_bytes + column / 8];
+                    usize::from(byte & (0x80 >> (column % 8)) == 0)
+                })
+                .collect()
+        }
+        _ => {
+            let sample_bytes = if max_value > 255 { 2 } else { 1 };
+            let raster = reader.raster();
+            if raster.len() < samples * sample_bytes {
+                return Err("The image has fewer pixels than its size");
+            }
+            raster
+                .chunks_exact(sample_bytes)
+                .take(samples)
+                .map(|sample| {
+                    sample
+                        .iter()
+                        .fold(0, |value, &byte| value << 8 | usize::from(byte))
+                })
+                .collect()
+        }
+    };
+
+    let scale = |value: usize| (value.min(max_value) * 255 / max_value) as u8;
+    let pixels = values
+        .chunks_exact(channels)
+        .map(|pixel| match pixel {
+            [gray] => [scale(*gray); 3],
+            [red, green, blue] => [scale(*red), scale(*green), scale(*blue)],
+            _ => unreachable!(),
+        })
+        .collect();
+
+    Ok(Image {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// Browsers can't show PNM images, but they can show uncompressed BMP images, which are easy
+/// to write
+fn to_bmp(image: &Image) -> Vec<u8> {
+    const HEADER_SIZE: usize = 14 + 40;
+    let row_size = (image.width * 3).div_ceil(4) * 4;
+    let image_size = row_size * image.height;
+
+    let mut bmp = Vec::with_capacity(HEADER_SIZE + image_size);
+    bmp.extend_from_slice(b"BM");
+    bmp.extend_from_slice(&((HEADER_SIZE + image_size) as u32).to_le_bytes());
+    bmp.extend_from_slice(&0u32.to_le_bytes());
+    bmp.extend_from_slice(&(HEADER_SIZE as u32).to_le_bytes());
+
+    bmp.extend_from_slice(&40u32.to_le_bytes());
+    bmp.extend_from_slice(&(image.width as i32).to_le_bytes());
+    // A negative height stores the rows from top to bottom
+    bmp.extend_from_slice(&(-(image.height as i32)).to_le_bytes());
+    bmp.extend_from_slice(&1u16.to_le_bytes());
+    bmp.extend_from_slice(&24u16.to_le_bytes());
+    bmp.extend_from_slice(&0u32.to_le_bytes());
+    bmp.extend_from_slice(&(image_size as u32).to_le_bytes());
+    bmp.extend_from_slice(&2835u32.to_le_bytes());
+    bmp.extend_from_slice(&2835u32.to_le_bytes());
+    bmp.extend_from_slice(&0u32.to_le_bytes());
+    bmp.extend_from_slice(&0u32.to_le_bytes());
+
+    for row in image.pixels.chunks_exact(image.width) {
+        for [red, green, blue] in row {
+            bmp.extend_from_slice(&[*blue, *green, *red]);
+        }
+        bmp.resize(bmp.len() + row_size - image.width * 3, 0);
+    }
+
+    bmp
+}
+
+fn render_image(column: usize, image: &str) -> Field {
+    let data = from_binary_string(image).unwrap_or_else(|| image.as_bytes().to_vec());
+
+    match parse_pnm(&data) {
+        Ok(image) => Field {
+            column,
+            span: 1,
+            content: format!(
+                "data:image/bmp;base64,{}",
+                BASE64_STANDARD.encode(to_bmp(&image))
+            ),
+            kind: FieldKind::Image,
+            row_span: 1,
+            spans: vec![],
+        },
+        Err(error) => Field {
+            column,
+            span: 1,
+            content: format!("Could not show the image: {error}"),
+            kind: FieldKind::Meta,
+            row_span: 1,
+            spans: vec![],
+        },
+    }
+}
+
+pub fn render_image_display_mode(output: String, expected: Option<String>) -> Columns {
+    let mut column_titles = vec![Some("Output".into())];
+    let mut fields = vec![render_image(0, &output)];
+
+    if let Some(expected) = expected {
+        column_titles.push(Some("Expected".into()));
+        fields.push(render_image(1, &expected));
+    }
+
+    Columns {
+        column_titles,
+        fields,
+        height: 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: [u8; 3] = [0, 0, 0];
+    const WHITE: [u8; 3] = [255, 255, 255];
+
+    fn pixels(data: &[u8]) -> (usize, usize, Vec<[u8; 3]>) {
+        let image = parse_pnm(data).unwrap();
+        (image.width, image.height, image.pixels)
+    }
+
+    #[test]
+    fn p1_allows_comments_and_packed_pixels() {
+        assert_eq!(
+            pixels(b"P1\n# a comment\n3 2\n1 0 1\n010"),
+            (3, 2, vec![BLACK, WHITE, BLACK, WHITE, BLACK, WHITE])
+        );
+    }
+
+    #[test]
+    fn p1_needs_every_pixel() {
+        assert!(parse_pnm(b"P1 2 2 1 0 1").is_err());
+        assert!(parse_pnm(b"P1 1 1 2").is_err());
+    }
+
+    #[test]
+    fn p4_rows_are_padded_to_whole_bytes() {
+        // 10 pixels per row take 2 bytes, the last 6 bits of each row are padding
+        assert_eq!(
+            pixels(b"P4\n10 2\n\x80\x40\x7f\xff"),
+            (
+                10,
+                2,
+                [
+                    [BLACK, WHITE, WHITE, WHITE, WHITE],
+                    [WHITE, WHITE, WHITE, WHITE, BLACK],
+                    [WHITE, BLACK, BLACK, BLACK, BLACK],
+                    [BLACK, BLACK, BLACK, BLACK, BLACK],
+                ]
+                .concat()
+            )
+        );
+    }
+
+    #[test]
+    fn p4_needs_every_row() {
+        assert!(parse_pnm(b"P4\n10 2\n\x80\x40\x7f").is_err());
+    }
+
+    #[test]
+    fn p6_reads_one_byte_samples() {
+        assert_eq!(
+            pixels(b"P6\n2 1\n255\n\xff\x00\x00\x00\x80\xff"),
+            (2, 1, vec![[255, 0, 0], [0, 128, 255]])
+        );
+    }
+
+    #[test]
+    fn p6_reads_big_endian_two_byte_samples() {
+        assert_eq!(
+            pixels(b"P6 1 1 65535\n\xff\xff\x80\x00\x00\x00"),
+            (1, 1, vec![[255, 127, 0]])
+        );
+    }
+
+    #[test]
+    fn p6_raster_starts_after_one_whitespace_character() {
+        // The second space is the first sample
+        assert_eq!(pixels(b"P6 1 1 255  AB"), (1, 1, vec![[32, 65, 66]]));
+        assert!(parse_pnm(b"P6 1 1 255 AB").is_err());
+    }
+
+    #[test]
+    fn ascii_formats_scale_to_the_max_value() {
+        assert_eq!(pixels(b"P2 2 1 4 0 4"), (2, 1, vec![BLACK, WHITE]));
+        assert_eq!(pixels(b"P3 1 1 10 10 5 0"), (1, 1, vec![[255, 127, 0]]));
+    }
+
+    #[test]
+    fn rejects_bad_headers() {
+        assert!(parse_pnm(b"P7 1 1 255 ").is_err());
+        assert!(parse_pnm(b"hello").is_err());
+        assert!(parse_pnm(b"P1 0 1").is_err());
+        assert!(parse_pnm(b"P1 513 1").is_err());
+        assert!(parse_pnm(b"P2 1 1 0 0").is_err());
+        assert!(parse_pnm(b"P2 1 1 65536 0").is_err());
+    }
+}
@@ -1,3 +1,7 @@
 pub mod diff_display_mode;
 pub mod filter_display_mode;
+pub mod grid_display_mode;
+pub mod hexdump_display_mode;
+pub mod image_display_mode;
+pub mod table_display_mode;
 pub mod test_case_display_mode;
@@ -0,0 +1,41 @@
+use common::{
+    MAX_TABLE_COLUMNS, MAX_TABLE_ROWS,
+    diff_tools::{Columns, Field, FieldKind},
+};
+
+pub fn render_table_display_mode(headers: Vec<String>, mut rows: Vec<Vec<String>>) -> Columns {
+    rows.truncate(MAX_TABLE_ROWS);
+    let width = rows
+        .iter()
+        .map(Vec::len)
+        .chain([headers.len()])
+        .max()
+        .unwrap_or(0)
+        .min(MAX_TABLE_COLUMNS);
+
+    let mut column_titles: Vec<_> = headers
+        .into_iter()
+        .take(width)
+        .map(|header| Some(header.into()))
+        .collect();
+    column_titles.resize(width, None);
+
+    Columns {
+        column_titles,
+        height: rows.len(),
+        fields: rows
+            .into_iter()
+            .flat_map(|mut row| {
+                row.resize(width, String::new());
+                row.into_iter().enumerate().map(|(column, content)| Field {
+                    column,
+                    span: 1,
+                    content,
+                    kind: FieldKind::Identical,
+                    row_span: 1,
+                    spans: vec![],
+                })
+            })
+            .collect(),
+    }
+}
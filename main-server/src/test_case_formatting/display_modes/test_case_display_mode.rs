@@ -1,4 +1,6 @@
-use common::diff_tools::{Columns, Field, FieldKind, FilterIteratorButKeepContext};
+use common::diff_tools::{
+    Columns, Field, FieldKind, FilterIteratorButKeepContext, intra_item_diff,
+};
 use itertools::Itertools;
 
 pub fn render_test_case_display_mode(
@@ -31,11 +33,18 @@ pub fn render_test_case_display_mode(
 
     let fields: Vec<Field> = FilterIteratorButKeepContext::new(
         iter.map(|(input, output, expected)| {
+            let (output_spans, expected_spans) = if output == expected {
+                (vec![], vec![])
+            } else {
+                intra_item_diff(output, expected).unwrap_or_default()
+            };
+
             vec![
                 Field {
                     column: 0,
                     span: 1,
                     row_span: 1,
+                    spans: vec![],
                     content: input.to_owned(),
                     kind: FieldKind::Identical,
                 },
@@ -43,6 +52,7 @@ pub fn render_test_case_display_mode(
                     column: 1,
                     span: 1,
                     row_span: 1,
+                    spans: output_spans,
                     content: output.to_owned(),
                     kind: if output == expected {
                         FieldKind::Identical
@@ -54,6 +64,7 @@ pub fn render_test_case_display_mode(
                     column: 2,
                     span: 1,
                     row_span: 1,
+                    spans: expected_spans,
                     content: expected.to_owned(),
                     kind: if output == expected {
                         FieldKind::Identical
@@ -69,6 +80,7 @@ pub fn render_test_case_display_mode(
                 column: 0,
                 span: 3,
                 row_span: 1,
+                spans: vec![],
                 content: format!("{rows_skipped} identical lines skipped"),
                 kind: FieldKind::Meta,
             }]
@@ -79,7 +91,11 @@ pub fn render_test_case_display_mode(
     .collect();
 
     Columns {
-        column_titles: vec![Some("Input"), Some("Output"), Some("Expected")],
+        column_titles: vec![
+            Some("Input".into()),
+            Some("Output".into()),
+            Some("Expected".into()),
+        ],
         height: fields.len(),
         fields,
     }
@@ -0,0 +1,166 @@
+use std::{
+    collections::VecDeque,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use common::diff_tools::{Field, FieldKind};
+use serde::Serialize;
+
+/// Rendering thousands of fields makes the page slow, only this many are sent at once
+pub const FIELDS_PER_PAGE: usize = 600;
+/// The fields that aren't sent right away are kept in memory, the oldest are dropped first
+const MAX_STORED_BYTES: usize = 64 * 1024 * 1024;
+/// So one account running the judge over and over can't push out the pages of everyone
+/// else. Logged out users share one account.
+const MAX_STORED_BYTES_PER_ACCOUNT: usize = 8 * 1024 * 1024;
+/// Nobody is going to expand a test case from a run this old
+const STORED_FIELDS_LIFETIME: Duration = Duration::from_secs(60 * 60);
+
+struct StoredFields {
+    id: String,
+    account: Option<i32>,
+    stored_at: Instant,
+    size: usize,
+    /// Starts at field `FIELDS_PER_PAGE` of the test case
+    fields: Vec<Field>,
+}
+
+static STORED_FIELDS: LazyLock<Mutex<VecDeque<StoredFields>>> =
+    LazyLock::new(|| Mutex::new(VecDeque::new()));
+
+/// Where to get the fields of a test case that weren't sent yet
+#[derive(Serialize, Clone)]
+pub struct MoreFields {
+    pub id: String,
+    /// Index of the next field in the test case
+    pub offset: usize,
+    pub remaining: usize,
+}
+
+#[derive(Serialize)]
+pub struct FieldPage {
+    pub fields: Vec<Field>,
+    pub more: Option<MoreFields>,
+}
+
+/// Roughly the memory a field takes, the highlighted spans repeat the content
+fn field_size(field: &Field) -> usize {
+    size_of::<Field>()
+        + field.content.len()
+        + field
+            .spans
+            .iter()
+            .map(|span| size_of_val(span) + span.content.len())
+            .sum::<usize>()
+}
+
+/// Drops the oldest entries matching `filter` until `size` more bytes fit in `limit`
+fn make_room(
+    stored: &mut VecDeque<StoredFields>,
+    filter: impl Fn(&StoredFields) -> bool,
+    size: usize,
+    limit: usize,
+) {
+    let mut used: usize = stored.iter().filter(|e| filter(e)).map(|e| e.size).sum();
+    while used + size > limit
+        && let Some(index) = stored.iter().position(&filter)
+    {
+        used -= stored[index].size;
+        stored.remove(index);
+    }
+}
+
+/// Keeps the first page of fields and stores the rest for `account` to fetch later. Fields
+/// past the share of the account are replaced by a note spanning all `columns`.
+pub fn split_first_page(
+    fields: &mut Vec<Field>,
+    columns: usize,
+    account: Option<i32>,
+) -> Option<MoreFields> {
+    if fields.len() <= FIELDS_PER_PAGE {
+        return None;
+    }
+
+    let mut rest = fields.split_off(FIELDS_PER_PAGE);
+    let mut size = 0;
+    let fitting = rest
+        .iter()
+        .take_while(|field| {
+            size += field_size(field);
+            size <= MAX_STORED_BYTES_PER_ACCOUNT
+        })
+        .count();
+    let dropped = rest.len() - fitting;
+    rest.truncate(fitting);
+    if dropped > 0 {
+        let note = Field {
+            kind: FieldKind::Meta,
+            column: 0,
+            span: columns.max(1),
+            content: format!(
+                "About {} more lines not shown, the output is too large",
+                dropped.div_ceil(columns.max(1))
+            ),
+            row_span: 1,
+            spans: vec![],
+        };
+        if rest.is_empty() {
+            fields.push(note);
+        } else {
+            rest.push(note);
+        }
+    }
+    if rest.is_empty() {
+        return None;
+    }
+    let size = rest.iter().map(field_size).sum();
+
+    let more = MoreFields {
+        id: uuid::Uuid::new_v4().to_string(),
+        offset: FIELDS_PER_PAGE,
+        remaining: rest.len(),
+    };
+
+    let mut stored = STORED_FIELDS.lock().unwrap();
+    stored.retain(|e| e.stored_at.elapsed() < STORED_FIELDS_LIFETIME);
+    make_room(
+        &mut stored,
+        |e| e.account == account,
+        size,
+        MAX_STORED_BYTES_PER_ACCOUNT,
+    );
+    make_room(&mut stored, |_| true, size, MAX_STORED_BYTES);
+    stored.push_back(StoredFields {
+        id: more.id.clone(),
+        account,
+        stored_at: Instant::now(),
+        size,
+        fields: rest,
+    });
+
+    Some(more)
+}
+
+/// Returns `None` if the fields were dropped or the offset isn't the start of a page
+pub fn get_page(id: &str, offset: usize) -> Option<FieldPage> {
+    let stored = STORED_FIELDS.lock().unwrap();
+    let stored = stored
+        .iter()
+        .find(|e| e.id == id && e.stored_at.elapsed() < STORED_FIELDS_LIFETIME)?;
+
+    if offset < FIELDS_PER_PAGE || !offset.is_multiple_of(FIELDS_PER_PAGE) {
+        return None;
+    }
+    let start = offset - FIELDS_PER_PAGE;
+    let end = (start + FIELDS_PER_PAGE).min(stored.fields.len());
+    let fields = stored.fields.get(start..end)?.to_vec();
+
+    let more = (end < stored.fields.len()).then(|| MoreFields {
+        id: stored.id.clone(),
+        offset: offset + fields.len(),
+        remaining: stored.fields.len() - end,
+    });
+
+    Some(FieldPage { fields, more })
+}
@@ -1,14 +1,16 @@
 mod display_modes;
+pub mod field_pages;
 mod test_case_display;
+mod test_group_display;
 
 use common::{RunLangOutput, Timers};
 use serde::Serialize;
-use test_case_display::TestCaseDisplay;
+use test_group_display::TestGroupDisplay;
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OutputDisplay {
-    tests: Vec<TestCaseDisplay>,
+    groups: Vec<TestGroupDisplay>,
     passed: bool,
     timed_out: bool,
     judge_error: Option<String>,
@@ -16,23 +18,16 @@ pub struct OutputDisplay {
     points: Option<i32>,
 }
 
-impl From<RunLangOutput> for OutputDisplay {
-    fn from(value: RunLangOutput) -> Self {
+impl OutputDisplay {
+    /// `account` is who the results are for, large test cases are stored for them to page
+    /// through
+    pub fn new(value: RunLangOutput, account: Option<i32>) -> Self {
         OutputDisplay {
-            tests: value
-                .tests
-                .test_cases
-                .into_iter()
-                .map(TestCaseDisplay::from_test_case)
-                .map(|e| {
-                    // If the test passes, hide all info boxes
-                    if value.tests.pass {
-                        e.with_visible(false)
-                    } else {
-                        e
-                    }
-                })
-                .collect(),
+            groups: TestGroupDisplay::group_test_cases(
+                value.tests.test_cases,
+                value.tests.pass,
+                account,
+            ),
             passed: value.tests.pass,
             timed_out: value.timed_out,
             judge_error: (!value.stderr.is_empty()).then_some(value.stderr),
@@ -1,14 +1,24 @@
 use std::borrow::Cow;
 
 use common::{
-    ResultDisplay, TestCase, TestPassState,
+    ResultDisplay, RunExit, TestCase, TestPassState,
     diff_tools::{Columns, Field, FieldKind},
 };
 use serde::Serialize;
 
-use crate::test_case_formatting::display_modes::{
-    diff_display_mode::render_diff_display_mode, filter_display_mode::render_filter_display_mode,
-    test_case_display_mode::render_test_case_display_mode,
+use crate::test_case_formatting::{
+    display_modes::{
+        diff_display_mode::{
+            render_diff_display_mode, render_numeric_display_mode, render_unordered_display_mode,
+        },
+        filter_display_mode::render_filter_display_mode,
+        grid_display_mode::render_grid_display_mode,
+        hexdump_display_mode::render_hexdump_display_mode,
+        image_display_mode::render_image_display_mode,
+        table_display_mode::render_table_display_mode,
+        test_case_display_mode::render_test_case_display_mode,
+    },
+    field_pages::{MoreFields, split_first_page},
 };
 
 #[derive(Serialize)]
@@ -18,6 +28,8 @@ pub struct TestCaseDisplay {
     title: Option<Cow<'static, str>>,
     status: TestPassState,
     pub(super) default_visible: bool,
+    /// Set if there were too many fields to send at once
+    more_fields: Option<MoreFields>,
 }
 
 impl TestCaseDisplay {
@@ -36,6 +48,7 @@ impl TestCaseDisplay {
                     content: e,
                     kind: FieldKind::Identical,
                     row_span: 1,
+                    spans: vec![],
                 }],
                 height: 1,
             },
@@ -56,60 +69,100 @@ impl TestCaseDisplay {
                 common::DisplayMode::Test => {
                     render_test_case_display_mode(output, expected, sep, input, input_separator)
                 }
+                common::DisplayMode::Grid => render_grid_display_mode(output, expected, sep, input),
+                common::DisplayMode::Unordered => {
+                    render_unordered_display_mode(output, expected, sep, input)
+                }
+                common::DisplayMode::Numeric { tolerance } => {
+                    render_numeric_display_mode(output, expected, sep, tolerance, input)
+                }
             },
             common::ResultDisplay::Run {
                 input,
                 output,
                 error,
-            } => match input {
-                Some(input) => Columns {
-                    column_titles: vec![Some("Input"), Some("Output"), Some("Error")],
-                    fields: vec![
-                        Field {
-                            column: 0,
-                            span: 1,
-                            content: input,
-                            kind: FieldKind::Identical,
-                            row_span: 1,
-                        },
-                        Field {
-                            column: 1,
-                            span: 1,
-                            content: output,
-                            kind: FieldKind::Identical,
-                            row_span: 1,
-                        },
-                        Field {
-                            column: 2,
-                            span: 2,
-                            content: error,
-                            kind: FieldKind::Identical,
-                            row_span: 1,
-                        },
-                    ],
-                    height: 1,
-                },
-                None => Columns {
-                    column_titles: vec![Some("Output"), Some("Error")],
-                    fields: vec![
-                        Field {
-                            column: 0,
-                            span: 1,
-                            content: output,
-                            kind: FieldKind::Identical,
-                            row_span: 1,
-                        },
-                        Field {
-                            column: 1,
-                            span: 2,
-                            content: error,
-                            kind: FieldKind::Identical,
-                            row_span: 1,
-                        },
-                    ],
-                    height: 1,
-                },
-            },
+                exit,
+            } => {
+                let mut columns = match input {
+                    Some(input) => Columns {
+                        column_titles: vec![
+                            Some("Input".into()),
+                            Some("Output".into()),
+                            Some("Error".into()),
+                        ],
+                        fields: vec![
+                            Field {
+                                column: 0,
+                                span: 1,
+                                content: input,
+                                kind: FieldKind::Identical,
+                                row_span: 1,
+                                spans: vec![],
+                            },
+                            Field {
+                                column: 1,
+                                span: 1,
+                                content: output,
+                                kind: FieldKind::Identical,
+                                row_span: 1,
+                                spans: vec![],
+                            },
+                            Field {
+                                column: 2,
+                                span: 2,
+                                content: error,
+                                kind: FieldKind::Identical,
+                                row_span: 1,
+                                spans: vec![],
+                            },
+                        ],
+                        height: 1,
+                    },
+                    None => Columns {
+                        column_titles: vec![Some("Output".into()), Some("Error".into())],
+                        fields: vec![
+                            Field {
+                                column: 0,
+                                span: 1,
+                                content: output,
+                                kind: FieldKind::Identical,
+                                row_span: 1,
+                                spans: vec![],
+                            },
+                            Field {
+                                column: 1,
+                                span: 2,
+                                content: error,
+                                kind: FieldKind::Identical,
+                                row_span: 1,
+                                spans: vec![],
+                            },
+                        ],
+                        height: 1,
+                    },
+                };
+                if let Some(exit) = exit.filter(RunExit::is_abnormal) {
+                    columns.fields.push(Field {
+                        column: 0,
+                        span: columns.column_titles.len(),
+                        content: exit.to_string(),
+                        kind: FieldKind::Meta,
+                        row_span: 1,
+                        spans: vec![],
+                    });
+                    columns.height += 1;
+                }
+                columns
+            }
+            common::ResultDisplay::Table { headers, rows } => {
+                render_table_display_mode(headers, rows)
+            }
+            common::ResultDisplay::Hexdump { output, expected } => {
+                render_hexdump_display_mode(output, expected)
+            }
+            common::ResultDisplay::Image { output, expected } => {
+                render_image_display_mode(output, expected)
+            }
         }
     }
 
@@ -129,22 +182,27 @@ impl TestCaseDisplay {
                     input: _,
                     output: _,
                     error,
-                } => !error.is_empty(),
+                    exit,
+                } => !error.is_empty() || exit.as_ref().is_some_and(RunExit::is_abnormal),
                 _ => true,
             },
             TestPassState::Warning => true,
         }
     }
 
-    pub fn from_test_case(test_case: TestCase) -> Self {
+    /// `account` is who the results are for, see [`split_first_page`]
+    pub fn from_test_case(test_case: TestCase, account: Option<i32>) -> Self {
         let default_visible = Self::get_default_visible(&test_case);
-        let columns = Self::get_columns(test_case.result_display);
+        let mut columns = Self::get_columns(test_case.result_display);
+        let more_fields =
+            split_first_page(&mut columns.fields, columns.column_titles.len(), account);
 
         TestCaseDisplay {
             columns,
             title: test_case.name.map(Cow::Owned),
             status: test_case.pass,
             default_visible,
+            more_fields,
         }
     }
 }
@@ -0,0 +1,80 @@
+use common::{TestCase, TestPassState};
+use serde::Serialize;
+
+use crate::test_case_formatting::test_case_display::TestCaseDisplay;
+
+/// How many test cases of a group ended in each state
+#[derive(Serialize, Default)]
+pub struct TestGroupCounts {
+    pass: usize,
+    fail: usize,
+    warning: usize,
+    info: usize,
+}
+
+impl TestGroupCounts {
+    fn add(&mut self, state: &TestPassState) {
+        match state {
+            TestPassState::Pass => self.pass += 1,
+            TestPassState::Fail => self.fail += 1,
+            TestPassState::Warning => self.warning += 1,
+            TestPassState::Info => self.info += 1,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestGroupDisplay {
+    /// `None` for the test cases without a group, those aren't put in a collapsible section
+    name: Option<String>,
+    tests: Vec<TestCaseDisplay>,
+    counts: TestGroupCounts,
+    default_open: bool,
+}
+
+impl TestGroupDisplay {
+    /// Groups the test cases in the order each group first appears. Only the first group
+    /// with a failing test case is open by default.
+    pub fn group_test_cases(
+        test_cases: Vec<TestCase>,
+        passed: bool,
+        account: Option<i32>,
+    ) -> Vec<Self> {
+        let mut groups: Vec<TestGroupDisplay> = vec![];
+
+        for test_case in test_cases {
+            let index = match groups.iter().position(|e| e.name == test_case.group) {
+                Some(index) => index,
+                None => {
+                    groups.push(TestGroupDisplay {
+                        name: test_case.group.clone(),
+                        tests: vec![],
+                        counts: TestGroupCounts::default(),
+                        default_open: false,
+                    });
+                    groups.len() - 1
+                }
+            };
+            let group = &mut groups[index];
+
+            group.counts.add(&test_case.pass);
+            let test = TestCaseDisplay::from_test_case(test_case, account);
+            // If the test passes, hide all info boxes
+            group.tests.push(if passed {
+                test.with_visible(false)
+            } else {
+                test
+            });
+        }
+
+        if let Some(group) = groups
+            .iter_mut()
+            .find(|e| e.name.is_some() && e.counts.fail > 0)
+        {
+            group.default_open = true;
+        }
+
+        groups
+    }
+}
@@ -1,9 +1,41 @@
-use std::{borrow::Cow, time::Duration};
+use std::{
+    borrow::Cow,
+    time::{Duration, Instant},
+};
 
-use common::RunLangOutput;
-use serde::Serialize;
+use common::{
+    DEFAULT_TIMERS, InstalledLanguageVersion, JudgeDiagnostic, LanguageVersion, RunLangOutput,
+    RunLimits, SelfTestResult, UninstallResult,
+    langs::LANGS,
+    telemetry::{REQUEST_ID_HEADER, inject_trace_context},
+};
+use reqwest::{
+    RequestBuilder,
+    header::{HeaderMap, HeaderValue, RETRY_AFTER},
+};
+use serde::{Serialize, de::DeserializeOwned};
 
-use crate::error::Error;
+use crate::{
+    error::Error,
+    metrics::{record_lang_runner_request, record_lang_runner_retry},
+    request_id::current_request_id,
+};
+
+/// How long a request keeps being retried while the lang runner is restarting. The runner
+/// drains for up to 30 seconds (`DRAIN_TIMEOUT` in the lang runner), then has to start again.
+const RUNNER_RESTART_DEADLINE: Duration = Duration::from_secs(30 + 30);
+const RUNNER_RESTART_RETRY_DELAY: Duration = Duration::from_secs(2);
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(10);
+/// Starting the judge and sending back the results, on top of the time the judge gets
+const RUNNER_REQUEST_SLACK: Duration = Duration::from_secs(5);
+/// No judge request waits longer than this, whatever the limits of the challenge
+const MAX_RUNNER_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// The self test may need to install every language first
+const SELF_TEST_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+/// Type checking a judge loads the whole TypeScript compiler
+const CHECK_JUDGE_TIMEOUT: Duration = Duration::from_secs(40);
+/// Uninstalling has to wait for all running judges to finish first
+const ADMIN_REQUEST_TIMEOUT: Duration = Duration::from_secs(2 * 60);
 
 #[derive(Serialize)]
 struct TestRunnerRequest<'a> {
@@ -11,47 +43,258 @@ struct TestRunnerRequest<'a> {
     version: &'a str,
     code: &'a str,
     judge: &'a str,
+    binary: bool,
+    limits: RunLimits,
 }
 
+/// For binary solutions `code` has to hold one char per byte, see `SolutionCode::runner_code`
+#[tracing::instrument(skip(code, judge), fields(request_id = tracing::field::Empty))]
 pub async fn test_solution(
     code: &str,
     language: &str,
     version: &str,
     judge: &str,
+    binary: bool,
+    limits: RunLimits,
+) -> Result<RunLangOutput, Error> {
+    let request_id = current_request_id();
+    tracing::Span::current().record("request_id", &request_id);
+
+    let start_time = Instant::now();
+    let result =
+        test_solution_inner(code, language, version, judge, binary, limits, &request_id).await;
+
+    record_lang_runner_request(
+        language,
+        match &result {
+            Ok(output) if output.timed_out => "timeout",
+            Ok(output) if output.tests.pass => "pass",
+            Ok(_) => "fail",
+            Err(_) => "error",
+        },
+        start_time.elapsed(),
+    );
+
+    result
+}
+
+async fn test_solution_inner(
+    code: &str,
+    language: &str,
+    version: &str,
+    judge: &str,
+    binary: bool,
+    limits: RunLimits,
+    request_id: &str,
 ) -> Result<RunLangOutput, Error> {
     let client = reqwest::Client::new();
+    let deadline = Instant::now() + RUNNER_RESTART_DEADLINE;
+    let mut attempt = 1;
+
+    loop {
+        match send_to_runner(
+            &client, code, language, version, judge, binary, limits, request_id,
+        )
+        .await
+        {
+            Err(RunnerError::Retryable(retry_after, _)) if Instant::now() < deadline => {
+                tracing::info!(attempt, ?retry_after, "Lang runner unavailable, retrying");
+                record_lang_runner_retry(language);
+                tokio::time::sleep(
+                    retry_after.min(deadline.saturating_duration_since(Instant::now())),
+                )
+                .await;
+                attempt += 1;
+            }
+            Err(RunnerError::Retryable(_, error) | RunnerError::Fatal(error)) => return Err(error),
+            Ok(output) => return Ok(output),
+        }
+    }
+}
+
+enum RunnerError {
+    /// The runner is restarting or draining, the same request can be sent again after the delay
+    Retryable(Duration, Error),
+    Fatal(Error),
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn send_to_runner(
+    client: &reqwest::Client,
+    code: &str,
+    language: &str,
+    version: &str,
+    judge: &str,
+    binary: bool,
+    limits: RunLimits,
+    request_id: &str,
+) -> Result<RunLangOutput, RunnerError> {
+    let mut headers = HeaderMap::new();
+    if let Ok(request_id) = HeaderValue::from_str(request_id) {
+        headers.insert(REQUEST_ID_HEADER, request_id);
+    }
+    inject_trace_context(&mut headers);
+
     let resp = client
         .post("http://localhost:3000")
+        .headers(headers)
         .json(&TestRunnerRequest {
             lang: language,
             version,
             code,
             judge,
+            binary,
+            limits,
         })
-        .timeout(Duration::from_secs(10))
+        .timeout(runner_request_timeout(language, limits))
         .send()
         .await
         .map_err(|e| {
             if e.is_timeout() {
-                Error::RunLang(Cow::Borrowed(concat!(
+                RunnerError::Fatal(Error::RunLang(Cow::Borrowed(concat!(
                     "Timeout connecting to the lang runner, this usually means the language ",
                     "is in the process of being installed"
-                )))
+                ))))
+            } else if e.is_connect() {
+                // Most likely the runner is in the middle of restarting
+                RunnerError::Retryable(
+                    RUNNER_RESTART_RETRY_DELAY,
+                    Error::RunLang(Cow::Borrowed("Failed to connect to the lang runner")),
+                )
             } else {
-                Error::RunLang(Cow::Borrowed("Failed to connect to the lang runner"))
+                RunnerError::Fatal(Error::RunLang(Cow::Borrowed(
+                    "Failed to connect to the lang runner",
+                )))
             }
         })?;
 
     if !resp.status().is_success() {
-        return Err(Error::RunLang(Cow::Owned(
-            resp.text().await.map_err(|_| Error::ServerError)?,
-        )));
+        // The runner only sets Retry-After when it is draining
+        let retry_after = resp
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+            .map(|seconds: u64| Duration::from_secs(seconds).min(MAX_RETRY_AFTER));
+        let error = Error::RunLang(Cow::Owned(
+            resp.text()
+                .await
+                .map_err(|_| RunnerError::Fatal(Error::ServerError))?,
+        ));
+
+        return Err(match retry_after {
+            Some(retry_after) => RunnerError::Retryable(retry_after, error),
+            None => RunnerError::Fatal(error),
+        });
     }
 
     let out = resp
         .json::<RunLangOutput>()
         .await
-        .map_err(|_| Error::RunLang(Cow::Borrowed("Failed to parse json")))?;
+        .map_err(|_| RunnerError::Fatal(Error::RunLang(Cow::Borrowed("Failed to parse json"))))?;
 
     Ok(out)
 }
+
+/// Waits for the runner to use all the time the judge gets, see `RunLimits::timers`
+fn runner_request_timeout(language: &str, limits: RunLimits) -> Duration {
+    let extra_runtime = LANGS
+        .get(language)
+        .map_or(&DEFAULT_TIMERS, |lang| &lang.extra_runtime);
+
+    (limits.timers(extra_runtime).total() + RUNNER_REQUEST_SLACK).min(MAX_RUNNER_REQUEST_TIMEOUT)
+}
+
+/// Makes sure a judge with the largest limits never runs into the request timeout in any
+/// language, called on startup
+pub fn check_runner_request_timeout() {
+    for (name, lang) in LANGS.entries() {
+        let needed = RunLimits::MAX.timers(&lang.extra_runtime).total() + RUNNER_REQUEST_SLACK;
+        assert!(
+            needed <= MAX_RUNNER_REQUEST_TIMEOUT,
+            "Judges in {name} can take {needed:?} with the largest run limits, which is more than the {MAX_RUNNER_REQUEST_TIMEOUT:?} request timeout"
+        );
+    }
+}
+
+#[derive(Serialize)]
+struct CheckJudgeRequest<'a> {
+    judge: &'a str,
+}
+
+/// Type checks a TypeScript judge, an empty list means it has no errors
+pub async fn check_judge(judge: &str) -> Result<Vec<JudgeDiagnostic>, Error> {
+    runner_admin_request(
+        reqwest::Client::new()
+            .post("http://localhost:3000/check-judge")
+            .json(&CheckJudgeRequest { judge })
+            .timeout(CHECK_JUDGE_TIMEOUT),
+    )
+    .await
+}
+
+/// The type errors in a judge, one per line. Problems reaching the runner are only logged,
+/// running the example code will report those anyway.
+pub async fn describe_judge_type_errors(judge: &str) -> Option<String> {
+    match check_judge(judge).await {
+        Ok(diagnostics) if diagnostics.is_empty() => None,
+        Ok(diagnostics) => Some(
+            diagnostics
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        Err(e) => {
+            eprintln!("Failed to type check judge: {e:?}");
+            None
+        }
+    }
+}
+
+pub async fn run_runner_self_test() -> Result<Vec<SelfTestResult>, Error> {
+    runner_admin_request(
+        reqwest::Client::new()
+            .post("http://localhost:3000/self-test")
+            .timeout(SELF_TEST_TIMEOUT),
+    )
+    .await
+}
+
+pub async fn get_installed_versions() -> Result<Vec<InstalledLanguageVersion>, Error> {
+    runner_admin_request(
+        reqwest::Client::new()
+            .get("http://localhost:3000/installed-versions")
+            .timeout(ADMIN_REQUEST_TIMEOUT),
+    )
+    .await
+}
+
+pub async fn uninstall_versions(
+    versions: &[LanguageVersion],
+) -> Result<Vec<UninstallResult>, Error> {
+    runner_admin_request(
+        reqwest::Client::new()
+            .post("http://localhost:3000/uninstall")
+            .json(versions)
+            .timeout(ADMIN_REQUEST_TIMEOUT),
+    )
+    .await
+}
+
+async fn runner_admin_request<T: DeserializeOwned>(request: RequestBuilder) -> Result<T, Error> {
+    let resp = request
+        .send()
+        .await
+        .map_err(|_| Error::RunLang(Cow::Borrowed("Failed to connect to the lang runner")))?;
+
+    if !resp.status().is_success() {
+        return Err(Error::RunLang(Cow::Owned(
+            resp.text().await.map_err(|_| Error::ServerError)?,
+        )));
+    }
+
+    resp.json::<T>()
+        .await
+        .map_err(|_| Error::RunLang(Cow::Borrowed("Failed to parse json")))
+}
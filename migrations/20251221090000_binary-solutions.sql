@@ -0,0 +1,3 @@
+-- Solutions submitted as raw bytes. For these `code` is left empty since text columns
+-- can not hold null bytes or invalid UTF-8.
+ALTER TABLE solutions ADD COLUMN binary_code bytea;
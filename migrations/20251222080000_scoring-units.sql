@@ -0,0 +1,14 @@
+CREATE TYPE scoring_unit AS ENUM ('bytes', 'characters', 'lines', 'tokens', 'points');
+
+-- The unit used to be free text that was only displayed, every solution was scored in bytes
+-- unless the judge returned points. Labels like 'chars' keep that scoring instead of switching to
+-- a unit the stored points weren't computed in, authors can pick the new units themselves.
+ALTER TABLE challenges ALTER COLUMN unit DROP DEFAULT;
+ALTER TABLE challenges ALTER COLUMN unit TYPE scoring_unit USING (
+    CASE lower(trim(unit))
+        WHEN 'bytes' THEN 'bytes'
+        WHEN 'byte' THEN 'bytes'
+        ELSE 'points'
+    END
+)::scoring_unit;
+ALTER TABLE challenges ALTER COLUMN unit SET DEFAULT 'bytes';
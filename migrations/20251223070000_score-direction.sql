@@ -0,0 +1,78 @@
+CREATE TYPE score_direction AS ENUM ('minimize', 'maximize');
+
+ALTER TABLE challenges ADD COLUMN score_direction score_direction NOT NULL DEFAULT 'minimize';
+
+-- Lower ranked points are always better, so ranking and percentiles don't need to care
+-- about the direction of a challenge
+CREATE FUNCTION ranked_points(points integer, direction score_direction) RETURNS integer
+    LANGUAGE SQL IMMUTABLE
+    AS $$ SELECT CASE WHEN direction = 'maximize' THEN -points ELSE points END $$;
+
+CREATE MATERIALIZED VIEW scores_new AS
+WITH ranks AS (
+    SELECT
+        solutions.id,
+        solutions.author,
+        solutions.challenge,
+        solutions.language,
+        ranked_points(solutions.points, challenges.score_direction) as points,
+        solutions.valid,
+        cast((SELECT COUNT(*) FROM solutions as s2 WHERE s2.language = solutions.language AND s2.challenge = solutions.challenge and s2.valid)as integer) as total_sols,
+        rank() OVER peers as rank
+    FROM solutions
+        INNER JOIN challenges ON solutions.challenge = challenges.id
+    WHERE solutions.valid and not solutions.is_post_mortem
+    window peers as (PARTITION BY solutions.language, solutions.challenge, solutions.valid ORDER BY ranked_points(solutions.points, challenges.score_direction) asc rows between unbounded preceding and unbounded following)
+),
+percentiles AS (
+    SELECT
+        id,
+        author,
+        challenge,
+        language,
+        points,
+        total_sols,
+        rank,
+        (case WHEN total_sols > 2
+        	then nth_value(points, total_sols / 2 + 1)
+            	OVER peers
+            else 9999
+         end) as percentile_50th,
+        (case WHEN total_sols > 1
+	        then nth_value(points, total_sols * 9 / 10 + 1)
+	            OVER peers
+	        else 9999
+	     end) as percentile_90th,
+        (case WHEN total_sols > 9 then 
+        	nth_value(points, total_sols / 10 + 1)
+            	OVER peers
+            else 9999
+        end) as percentile_10th
+    FROM ranks
+    window peers as (PARTITION BY language, challenge ORDER BY points asc rows between unbounded preceding and unbounded following)
+)
+SELECT
+    id,
+    author,
+    challenge,
+    language,
+    total_sols,
+    rank,
+    -- score consists of 4 parts
+    -- first, 10 points for all first place sols
+    (case when "rank" = 1 then 10 else 0 end) +
+    -- Next, 1/4 point for each byte over the bottom 90th percentile
+    least(greatest((coalesce(percentile_90th, 9999) - points), 0)/4, 50) +
+    -- Next, 1/2 point for each byte over the 50th percentile
+    least(greatest((coalesce(percentile_50th, 9999) - points), 0)/2, 50) +
+    -- Next, a byte for each point over the top 90th percentile
+    least(greatest((coalesce(percentile_10th, 9999) - points), 0), 49) +
+    1
+     as score
+FROM percentiles;
+
+-- Swap the views without touching user_scoring_info, it only depends on scores_view
+CREATE OR REPLACE VIEW scores_view AS SELECT * FROM scores_new;
+DROP MATERIALIZED VIEW scores;
+ALTER MATERIALIZED VIEW scores_new RENAME TO scores;
+CREATE UNIQUE INDEX scores_pk ON scores(id);
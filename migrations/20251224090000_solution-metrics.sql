@@ -0,0 +1,87 @@
+ALTER TABLE solutions ADD COLUMN metrics jsonb NOT NULL DEFAULT '{}' CHECK (jsonb_typeof(metrics) = 'object');
+
+UPDATE solutions SET metrics = jsonb_build_object('runtime', runtime);
+
+-- The name of a metric used to order solutions with equal points
+ALTER TABLE challenges ADD COLUMN tiebreaker text;
+ALTER TABLE challenges ADD COLUMN tiebreaker_direction score_direction NOT NULL DEFAULT 'minimize';
+
+-- Like ranked_points, lower is always better. Solutions without the metric sort last.
+CREATE FUNCTION ranked_metric(metrics jsonb, metric text, direction score_direction) RETURNS double precision
+    LANGUAGE SQL IMMUTABLE
+    AS $$
+        SELECT CASE
+            WHEN direction = 'maximize' THEN -(metrics ->> metric)::double precision
+            ELSE (metrics ->> metric)::double precision
+        END
+    $$;
+
+-- Rank solutions the same way as the leaderboard, so a solution that loses the tiebreaker
+-- doesn't get the points for first place
+CREATE MATERIALIZED VIEW scores_new AS
+WITH ranks AS (
+    SELECT
+        solutions.id,
+        solutions.author,
+        solutions.challenge,
+        solutions.language,
+        ranked_points(solutions.points, challenges.score_direction) as points,
+        solutions.valid,
+        cast((SELECT COUNT(*) FROM solutions as s2 WHERE s2.language = solutions.language AND s2.challenge = solutions.challenge and s2.valid)as integer) as total_sols,
+        rank() OVER peers as rank
+    FROM solutions
+        INNER JOIN challenges ON solutions.challenge = challenges.id
+    WHERE solutions.valid and not solutions.is_post_mortem
+    window peers as (PARTITION BY solutions.language, solutions.challenge, solutions.valid ORDER BY ranked_points(solutions.points, challenges.score_direction) asc, ranked_metric(solutions.metrics, challenges.tiebreaker, challenges.tiebreaker_direction) asc nulls last rows between unbounded preceding and unbounded following)
+),
+percentiles AS (
+    SELECT
+        id,
+        author,
+        challenge,
+        language,
+        points,
+        total_sols,
+        rank,
+        (case WHEN total_sols > 2
+        	then nth_value(points, total_sols / 2 + 1)
+            	OVER peers
+            else 9999
+         end) as percentile_50th,
+        (case WHEN total_sols > 1
+	        then nth_value(points, total_sols * 9 / 10 + 1)
+	            OVER peers
+	        else 9999
+	     end) as percentile_90th,
+        (case WHEN total_sols > 9 then 
+        	nth_value(points, total_sols / 10 + 1)
+            	OVER peers
+            else 9999
+        end) as percentile_10th
+    FROM ranks
+    window peers as (PARTITION BY language, challenge ORDER BY points asc rows between unbounded preceding and unbounded following)
+)
+SELECT
+    id,
+    author,
+    challenge,
+    language,
+    total_sols,
+    rank,
+    -- score consists of 4 parts
+    -- first, 10 points for all first place sols
+    (case when "rank" = 1 then 10 else 0 end) +
+    -- Next, 1/4 point for each byte over the bottom 90th percentile
+    least(greatest((coalesce(percentile_90th, 9999) - points), 0)/4, 50) +
+    -- Next, 1/2 point for each byte over the 50th percentile
+    least(greatest((coalesce(percentile_50th, 9999) - points), 0)/2, 50) +
+    -- Next, a byte for each point over the top 90th percentile
+    least(greatest((coalesce(percentile_10th, 9999) - points), 0), 49) +
+    1
+     as score
+FROM percentiles;
+
+CREATE OR REPLACE VIEW scores_view AS SELECT * FROM scores_new;
+DROP MATERIALIZED VIEW scores;
+ALTER MATERIALIZED VIEW scores_new RENAME TO scores;
+CREATE UNIQUE INDEX scores_pk ON scores(id);
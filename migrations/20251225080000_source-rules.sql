@@ -0,0 +1 @@
+ALTER TABLE challenges ADD COLUMN source_rules jsonb NOT NULL DEFAULT '{}' CHECK (jsonb_typeof(source_rules) = 'object');
@@ -0,0 +1,3 @@
+CREATE TYPE judge_format AS ENUM ('typescript', 'declarative');
+
+ALTER TABLE challenges ADD COLUMN judge_format judge_format NOT NULL DEFAULT 'typescript';
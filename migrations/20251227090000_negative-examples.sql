@@ -0,0 +1 @@
+ALTER TABLE challenges ADD COLUMN negative_examples text[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,13 @@
+CREATE TABLE flakiness_check_request (
+    id SERIAL NOT NULL PRIMARY KEY,
+    challenge INTEGER NOT NULL REFERENCES challenges(id),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    processed BOOLEAN NOT NULL DEFAULT false,
+    -- One entry per checked solution with how many of the runs passed
+    report JSONB NULL DEFAULT NULL
+);
+
+ALTER TABLE challenges
+    -- The share of checked solutions that both passed and failed, NULL until checked
+    ADD COLUMN flakiness REAL NULL DEFAULT NULL,
+    ADD COLUMN flakiness_checked_at TIMESTAMP WITH TIME ZONE NULL DEFAULT NULL;
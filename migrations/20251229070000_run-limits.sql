@@ -0,0 +1,2 @@
+-- Missing fields fall back to the defaults of `common::RunLimits`
+ALTER TABLE challenges ADD COLUMN run_limits JSONB NOT NULL DEFAULT '{}';
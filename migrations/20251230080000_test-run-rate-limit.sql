@@ -0,0 +1,3 @@
+-- Test runs don't save anything, so they get their own, more generous rate limit
+ALTER TABLE accounts ADD COLUMN test_run_window_start TIMESTAMPTZ NOT NULL DEFAULT NOW();
+ALTER TABLE accounts ADD COLUMN test_runs_in_window INTEGER NOT NULL DEFAULT 0;